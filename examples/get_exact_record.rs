@@ -11,6 +11,7 @@ use rustc_serialize::json;
 use rustorm::query::Query;
 use rustorm::query::{Filter,Equality};
 use rustorm::dao::{Dao,IsDao};
+use rustorm::database::DbError;
 use rustorm::pool::ManagedPool;
 use rustorm::em::EntityManager;
 use rustorm::table::{Table,Column};
@@ -26,12 +27,12 @@ pub struct Product {
 }
 
 impl IsDao for Product{
-    fn from_dao(dao:&Dao)->Self{
-        Product{
-            product_id: dao.get("product_id"),
-            name: dao.get("name"),
-            description: dao.get_opt("description"),
-        }
+    fn from_dao(dao:&Dao)->Result<Self, DbError>{
+        Ok(Product{
+            product_id: try!(dao.try_get("product_id")),
+            name: try!(dao.try_get("name")),
+            description: try!(dao.try_get_opt("description")),
+        })
     }
     fn to_dao(&self)->Dao{
         let mut dao = Dao::new();
@@ -63,6 +64,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:true, is_unique:false, not_null:true, is_inherited:false, 
+                    precision:None, scale:None,
                     default:Some("uuid_generate_v4()".to_string()),
                     comment:None,
                     foreign:None,
@@ -72,6 +74,7 @@ impl IsTable for Product{
                     data_type:"String".to_string(),
                     db_data_type:"character varying".to_string(),
                     is_primary:false, is_unique:false, not_null:true, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:None,
@@ -81,6 +84,7 @@ impl IsTable for Product{
                     data_type:"String".to_string(),
                     db_data_type:"character varying".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:None,
@@ -9,6 +9,7 @@ use chrono::offset::utc::UTC;
 
 use rustorm::query::Query;
 use rustorm::dao::{Dao,IsDao};
+use rustorm::database::DbError;
 use rustorm::pool::ManagedPool;
 use rustorm::table::{IsTable,Table};
 
@@ -29,21 +30,21 @@ struct Category {
 }
 
 impl IsDao for Category{
-    fn from_dao(dao:&Dao)->Self{
-        Category{
-            organization_id: dao.get_opt("organization_id"),
-            client_id: dao.get_opt("client_id"),
-            created: dao.get("created"),
-            created_by: dao.get_opt("created_by"),
-            updated: dao.get("updated"),
-            updated_by: dao.get_opt("updated_by"),
-            priority: dao.get_opt("priority"),
-            name: dao.get_opt("name"),
-            description: dao.get_opt("description"),
-            help: dao.get_opt("help"),
-            active: dao.get("active"),
-            category_id: dao.get("category_id"),
-        }
+    fn from_dao(dao:&Dao)->Result<Self, DbError>{
+        Ok(Category{
+            organization_id: try!(dao.try_get_opt("organization_id")),
+            client_id: try!(dao.try_get_opt("client_id")),
+            created: try!(dao.try_get("created")),
+            created_by: try!(dao.try_get_opt("created_by")),
+            updated: try!(dao.try_get("updated")),
+            updated_by: try!(dao.try_get_opt("updated_by")),
+            priority: try!(dao.try_get_opt("priority")),
+            name: try!(dao.try_get_opt("name")),
+            description: try!(dao.try_get_opt("description")),
+            help: try!(dao.try_get_opt("help")),
+            active: try!(dao.try_get("active")),
+            category_id: try!(dao.try_get("category_id")),
+        })
     }
     fn to_dao(&self)->Dao{
         let mut dao = Dao::new();
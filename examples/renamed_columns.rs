@@ -11,6 +11,7 @@ use rustc_serialize::json;
 use rustorm::query::Query;
 use rustorm::query::{Filter,Equality};
 use rustorm::dao::{Dao,IsDao};
+use rustorm::database::DbError;
 use rustorm::pool::ManagedPool;
 
 #[derive(Debug, Clone)]
@@ -20,11 +21,11 @@ pub struct Photo {
 }
 
 impl IsDao for Photo{
-    fn from_dao(dao:&Dao)->Self{
-        Photo{
-            photo_id: dao.get("photo_id"),
-            url: dao.get_opt("url"),
-        }
+    fn from_dao(dao:&Dao)->Result<Self, DbError>{
+        Ok(Photo{
+            photo_id: try!(dao.try_get("photo_id")),
+            url: try!(dao.try_get_opt("url")),
+        })
     }
     fn to_dao(&self)->Dao{
         let mut dao = Dao::new();
@@ -60,7 +61,7 @@ fn main(){
         .asc("product.name")
         .desc("product.created")
         ;
-    let frag = query.build(db.as_ref());
+    let frag = query.build(db.as_ref()).unwrap();
     
     let expected = "
 SELECT product.product_id AS product_product_id, product.name AS product_name, category.product_id AS category_product_id, 
@@ -7,10 +7,12 @@ use uuid::Uuid;
 use chrono::datetime::DateTime;
 use chrono::offset::utc::UTC;
 use rustc_serialize::json;
+use rustc_serialize::json::Json;
 
 use rustorm::query::Query;
 use rustorm::query::{Filter,Equality};
 use rustorm::dao::{Dao,IsDao};
+use rustorm::database::DbError;
 use rustorm::pool::ManagedPool;
 use rustorm::database::Database;
 use rustorm::table::IsTable;
@@ -25,7 +27,7 @@ fn main(){
     let url = "sqlite:///file1.db";
     let mut pool = ManagedPool::init(&url, 1).unwrap();
     let db = pool.connect().unwrap();
-    db.as_ddl().create_table(&Product::table());
+    db.as_ddl().create_table(&Product::table()).unwrap();
 }
 
 
@@ -33,7 +35,7 @@ fn main(){
 ///
 /// This will be exposed as an @Api, including @Table(users, category, product_availability, photo)
 ///
-#[derive(RustcDecodable, RustcEncodable)]
+#[derive(RustcEncodable)]
 #[derive(Debug, Clone)]
 pub struct Product {
     /// primary
@@ -53,7 +55,7 @@ pub struct Product {
     /// weightUnit:"kg"
     /// }
     /// db data type: json
-    pub info: Option<String>,
+    pub info: Option<Json>,
     /// default: false
     /// db data type: boolean
     pub is_service: Option<bool>,
@@ -134,33 +136,33 @@ pub struct Product {
 
 
 impl IsDao for Product{
-    fn from_dao(dao:&Dao)->Self{
-        Product{
-            organization_id: dao.get_opt("organization_id"),
-            client_id: dao.get_opt("client_id"),
-            created: dao.get("created"),
-            created_by: dao.get_opt("created_by"),
-            updated: dao.get("updated"),
-            updated_by: dao.get_opt("updated_by"),
-            priority: dao.get_opt("priority"),
-            name: dao.get_opt("name"),
-            description: dao.get_opt("description"),
-            help: dao.get_opt("help"),
-            active: dao.get("active"),
-            product_id: dao.get("product_id"),
-            parent_product_id: dao.get_opt("parent_product_id"),
-            is_service: dao.get_opt("is_service"),
-            price: dao.get_opt("price"),
-            use_parent_price: dao.get_opt("use_parent_price"),
-            unit: dao.get_opt("unit"),
-            tags: dao.get_opt("tags"),
-            info: dao.get_opt("info"),
-            seq_no: dao.get_opt("seq_no"),
-            upfront_fee: dao.get_opt("upfront_fee"),
-            barcode: dao.get_opt("barcode"),
-            owner_id: dao.get_opt("owner_id"),
-            currency_id: dao.get_opt("currency_id"),
-        }
+    fn from_dao(dao:&Dao)->Result<Self, DbError>{
+        Ok(Product{
+            organization_id: try!(dao.try_get_opt("organization_id")),
+            client_id: try!(dao.try_get_opt("client_id")),
+            created: try!(dao.try_get("created")),
+            created_by: try!(dao.try_get_opt("created_by")),
+            updated: try!(dao.try_get("updated")),
+            updated_by: try!(dao.try_get_opt("updated_by")),
+            priority: try!(dao.try_get_opt("priority")),
+            name: try!(dao.try_get_opt("name")),
+            description: try!(dao.try_get_opt("description")),
+            help: try!(dao.try_get_opt("help")),
+            active: try!(dao.try_get("active")),
+            product_id: try!(dao.try_get("product_id")),
+            parent_product_id: try!(dao.try_get_opt("parent_product_id")),
+            is_service: try!(dao.try_get_opt("is_service")),
+            price: try!(dao.try_get_opt("price")),
+            use_parent_price: try!(dao.try_get_opt("use_parent_price")),
+            unit: try!(dao.try_get_opt("unit")),
+            tags: try!(dao.try_get_opt("tags")),
+            info: try!(dao.try_get_opt("info")),
+            seq_no: try!(dao.try_get_opt("seq_no")),
+            upfront_fee: try!(dao.try_get_opt("upfront_fee")),
+            barcode: try!(dao.try_get_opt("barcode")),
+            owner_id: try!(dao.try_get_opt("owner_id")),
+            currency_id: try!(dao.try_get_opt("currency_id")),
+        })
     }
 
     fn to_dao(&self)->Dao{
@@ -270,6 +272,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("@Value(users.user_id) , which means the value will be set with the users.user_id value\n\n@Where(users.active=true)".to_string()),
                     foreign:None,
@@ -279,6 +282,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("@Value(users.client_id) The client_id of the user creating this records".to_string()),
                     foreign:None,
@@ -288,6 +292,7 @@ impl IsTable for Product{
                     data_type:"DateTime<UTC>".to_string(),
                     db_data_type:"timestamp with time zone".to_string(),
                     is_primary:false, is_unique:false, not_null:true, is_inherited:true, 
+                    precision:None, scale:None,
                     default:Some("now()".to_string()),
                     comment:None,
                     foreign:None,
@@ -297,6 +302,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("@Value(users.user_id)".to_string()),
                     foreign:None,
@@ -306,6 +312,7 @@ impl IsTable for Product{
                     data_type:"DateTime<UTC>".to_string(),
                     db_data_type:"timestamp with time zone".to_string(),
                     is_primary:false, is_unique:false, not_null:true, is_inherited:true, 
+                    precision:None, scale:None,
                     default:Some("now()".to_string()),
                     comment:None,
                     foreign:None,
@@ -315,6 +322,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("@Value(users.user_id)".to_string()),
                     foreign:None,
@@ -324,6 +332,7 @@ impl IsTable for Product{
                     data_type:"f64".to_string(),
                     db_data_type:"numeric".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:None,
@@ -333,6 +342,7 @@ impl IsTable for Product{
                     data_type:"String".to_string(),
                     db_data_type:"character varying".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("This is @Required it has @DisplayLength(50) - 50 character in display length a @MinLength(1) and @MaxLength(100) - Do not go over 100 characters or else the system will throw a ValueTooLong exception\ncan also be express with @Length(1-100)".to_string()),
                     foreign:None,
@@ -342,6 +352,7 @@ impl IsTable for Product{
                     data_type:"String".to_string(),
                     db_data_type:"character varying".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("@DisplayLength(100) When building a UI for this field\n@MaxLength(200) Do not go over 200 character on this one".to_string()),
                     foreign:None,
@@ -351,6 +362,7 @@ impl IsTable for Product{
                     data_type:"String".to_string(),
                     db_data_type:"text".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:true, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:None,
@@ -360,6 +372,7 @@ impl IsTable for Product{
                     data_type:"bool".to_string(),
                     db_data_type:"boolean".to_string(),
                     is_primary:false, is_unique:false, not_null:true, is_inherited:true, 
+                    precision:None, scale:None,
                     default:Some("true".to_string()),
                     comment:Some("@Active".to_string()),
                     foreign:None,
@@ -369,6 +382,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:true, is_unique:false, not_null:true, is_inherited:false, 
+                    precision:None, scale:None,
                     default:Some("uuid_generate_v4()".to_string()),
                     comment:None,
                     foreign:None,
@@ -378,6 +392,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:None,
@@ -387,6 +402,7 @@ impl IsTable for Product{
                     data_type:"bool".to_string(),
                     db_data_type:"boolean".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:Some("false".to_string()),
                     comment:None,
                     foreign:None,
@@ -396,6 +412,7 @@ impl IsTable for Product{
                     data_type:"f64".to_string(),
                     db_data_type:"numeric".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:None,
@@ -405,6 +422,7 @@ impl IsTable for Product{
                     data_type:"bool".to_string(),
                     db_data_type:"boolean".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:Some("false".to_string()),
                     comment:None,
                     foreign:None,
@@ -414,6 +432,7 @@ impl IsTable for Product{
                     data_type:"String".to_string(),
                     db_data_type:"character varying".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:None,
@@ -423,15 +442,17 @@ impl IsTable for Product{
                     data_type:"String".to_string(),
                     db_data_type:"json".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:None,
                 },
                 Column{
                     name:"info".to_string(),
-                    data_type:"String".to_string(),
+                    data_type:"Json".to_string(),
                     db_data_type:"json".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("{color:\"red\",\ndimension:\"10x20x30\",\ndimensionUnit:\"mm\",\nweight:\"4\",\nweightUnit:\"kg\"\n}".to_string()),
                     foreign:None,
@@ -441,6 +462,7 @@ impl IsTable for Product{
                     data_type:"i32".to_string(),
                     db_data_type:"integer".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("@Sequence can be used to do alternate ordering of the values, when alphetical or time can not be used".to_string()),
                     foreign:None,
@@ -450,6 +472,7 @@ impl IsTable for Product{
                     data_type:"f64".to_string(),
                     db_data_type:"numeric".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:Some("0.00".to_string()),
                     comment:Some("Applicable to services, usually services has an upfront fee".to_string()),
                     foreign:None,
@@ -459,6 +482,7 @@ impl IsTable for Product{
                     data_type:"String".to_string(),
                     db_data_type:"character varying".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("barcode if scanning the product, conflict can happen, expect to return matching list of products using the barcode".to_string()),
                     foreign:None,
@@ -468,6 +492,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:Some("Whom this product belongs, since created_by can be someone else create the product list in behalf of the owner of the product".to_string()),
                     foreign:Some(
@@ -482,6 +507,7 @@ impl IsTable for Product{
                     data_type:"Uuid".to_string(),
                     db_data_type:"uuid".to_string(),
                     is_primary:false, is_unique:false, not_null:false, is_inherited:false, 
+                    precision:None, scale:None,
                     default:None,
                     comment:None,
                     foreign:Some(
@@ -12,7 +12,7 @@ fn main(){
     let db = pool.connect().unwrap();
     
     //let table = db.as_dev().get_table_metadata("","product_availability", false);
-    let table = db.as_dev().get_table_metadata("","product", false);
+    let table = db.as_dev().get_table_metadata("","product", false).unwrap();
     
     println!("table: {:#?}", table);
 }
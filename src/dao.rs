@@ -11,6 +11,10 @@ use query::ColumnName;
 use table::IsTable;
 use rustc_serialize::{Decodable, Encodable,Encoder,Decoder};
 use rustc_serialize::json::{self, ToJson, Json};
+use database::{DbError, DbErrorKind};
+use std::error::Error;
+use std::str::FromStr;
+use std::iter;
 
 
 #[derive(Debug)]
@@ -29,8 +33,10 @@ pub enum Type{
     U64,
     F32,
     F64,
+    Decimal,
     String,
     VecU8,
+    Array,
     Object,
     Json,
     Uuid,
@@ -57,8 +63,11 @@ pub enum Value{
     U64(u64),
     F32(f32),
     F64(f64),
+    Decimal(Decimal),
     String(String),
     VecU8(Vec<u8>),
+    /// one dimensional array, ie: postgresql text[], uuid[]
+    Array(Vec<Value>),
     Object(BTreeMap<String, Value>),
     Json(Json),
     Uuid(Uuid),
@@ -87,8 +96,10 @@ impl Encodable for Value{
             Value::U64(ref x) => x.encode(s),
             Value::F32(ref x) => x.encode(s),
             Value::F64(ref x) => x.encode(s),
+            Value::Decimal(ref x) => x.to_string().encode(s),
             Value::String(ref x) => x.encode(s),
             Value::VecU8(ref x) => x.encode(s),
+            Value::Array(ref x) => x.encode(s),
             Value::Uuid(ref x) => x.encode(s),
             Value::DateTime(ref x) => {
                 println!("encoding date time: {}", x.to_rfc3339());
@@ -118,8 +129,10 @@ impl ToJson for Value{
             Value::U64(ref x) => x.to_json(),
             Value::F32(ref x) => x.to_json(),
             Value::F64(ref x) => x.to_json(),
+            Value::Decimal(ref x) => x.to_string().to_json(),
             Value::String(ref x) => x.to_json(),
             Value::VecU8(ref x) => x.to_json(),
+            Value::Array(ref x) => Json::Array(x.iter().map(|v| v.to_json()).collect()),
             Value::Uuid(ref x) => x.to_hyphenated_string().to_json(),
             Value::DateTime(ref x) => x.to_rfc3339().to_json(),
 //            Value::NaiveDate(ref x) => x.to_json(),
@@ -147,8 +160,10 @@ impl fmt::Display for Value{
             Value::U16(ref x) => write!(f, "'{}'", x),
             Value::U32(ref x) => write!(f, "'{}'", x),
             Value::U64(ref x) => write!(f, "'{}'", x),
+            Value::Decimal(ref x) => write!(f, "'{}'", x),
             Value::String(ref x) => write!(f, "'{}'", x),
             Value::VecU8(ref x) => write!(f, "'{:?}'", x),
+            Value::Array(ref x) => write!(f, "'{:?}'", x),
             Value::Uuid(ref x) => write!(f, "'{}'", x),
             Value::DateTime(ref x) => write!(f, "'{}'", x),
             Value::NaiveDate(ref x) => write!(f, "'{}'", x),
@@ -162,6 +177,154 @@ impl fmt::Display for Value{
     }
 }
 
+impl Value{
+
+    /// the name of the variant, used in describing conversion errors
+    pub fn variant_name(&self)->&'static str{
+        match *self {
+            Value::Bool(_) => "Bool",
+            Value::I8(_) => "I8",
+            Value::I16(_) => "I16",
+            Value::I32(_) => "I32",
+            Value::I64(_) => "I64",
+            Value::U8(_) => "U8",
+            Value::U16(_) => "U16",
+            Value::U32(_) => "U32",
+            Value::U64(_) => "U64",
+            Value::F32(_) => "F32",
+            Value::F64(_) => "F64",
+            Value::Decimal(_) => "Decimal",
+            Value::String(_) => "String",
+            Value::VecU8(_) => "VecU8",
+            Value::Array(_) => "Array",
+            Value::Object(_) => "Object",
+            Value::Json(_) => "Json",
+            Value::Uuid(_) => "Uuid",
+            Value::DateTime(_) => "DateTime",
+            Value::NaiveDate(_) => "NaiveDate",
+            Value::NaiveTime(_) => "NaiveTime",
+            Value::NaiveDateTime(_) => "NaiveDateTime",
+            Value::Null => "Null",
+        }
+    }
+}
+
+
+/// an exact decimal number, for the NUMERIC and DECIMAL columns where f64 would lose the cents.
+/// kept as the digits of the unscaled value and the number of digits after the decimal point,
+/// ie: 123.45 is the digits 12345 with the scale of 2
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Decimal{
+    negative: bool,
+    /// the digits of the unscaled value, without the leading zeros
+    digits: String,
+    scale: u32,
+}
+
+impl Decimal{
+
+    /// the decimal of unscaled * 10^-scale, ie: Decimal::new(12345, 2) is 123.45
+    pub fn new(unscaled: i64, scale: u32)->Self{
+        let digits = if unscaled == 0 { String::new() } else { format!("{}", unscaled).trim_left_matches('-').to_string() };
+        Decimal{negative: unscaled < 0, digits: digits, scale: scale}
+    }
+
+    /// the number of digits after the decimal point
+    pub fn scale(&self)->u32{
+        self.scale
+    }
+
+    pub fn is_negative(&self)->bool{
+        self.negative
+    }
+
+    /// the integer part and the fraction part with exactly `scale` digits
+    pub fn parts(&self)->(String, String){
+        let scale = self.scale as usize;
+        let padded = if self.digits.len() <= scale{
+            let zeros: String = iter::repeat('0').take(scale + 1 - self.digits.len()).collect();
+            format!("{}{}", zeros, self.digits)
+        }else{
+            self.digits.clone()
+        };
+        let (integer, fraction) = padded.split_at(padded.len() - scale);
+        (integer.to_string(), fraction.to_string())
+    }
+
+    /// the same value without the trailing zeros after the decimal point, ie: 1.50 is 1.5
+    fn normalized(&self)->Decimal{
+        let mut digits = self.digits.clone();
+        let mut scale = self.scale;
+        while scale > 0 && digits.ends_with('0'){
+            digits.pop();
+            scale -= 1;
+        }
+        Decimal{negative: self.negative, digits: digits, scale: scale}
+    }
+
+    /// the value as i64, only when it has no fraction and is within the range
+    pub fn to_i64(&self)->Option<i64>{
+        let normalized = self.normalized();
+        if normalized.scale != 0{
+            return None;
+        }
+        normalized.to_string().parse::<i64>().ok()
+    }
+
+    /// the nearest f64 of this value
+    pub fn to_f64(&self)->f64{
+        self.to_string().parse::<f64>().unwrap_or(0.0)
+    }
+}
+
+impl FromStr for Decimal{
+    type Err = DbError;
+
+    /// parse decimal numbers such as `-123.45`, `.5`, `10.`
+    fn from_str(s: &str)->Result<Self, DbError>{
+        let trimmed = s.trim();
+        let (negative, unsigned) = if trimmed.starts_with('-'){
+            (true, &trimmed[1..])
+        }else if trimmed.starts_with('+'){
+            (false, &trimmed[1..])
+        }else{
+            (false, trimmed)
+        };
+        let (integer, fraction) = match unsigned.find('.'){
+            Some(dot) => (&unsigned[..dot], &unsigned[dot + 1..]),
+            None => (unsigned, ""),
+        };
+        let is_digits = |part: &str| part.chars().all(|c| c.is_digit(10));
+        if integer.len() + fraction.len() == 0 || !is_digits(integer) || !is_digits(fraction){
+            return Err(parse_error("Decimal", s));
+        }
+        let digits = format!("{}{}", integer, fraction).trim_left_matches('0').to_string();
+        Ok(Decimal{negative: negative && !digits.is_empty(), digits: digits, scale: fraction.len() as u32})
+    }
+}
+
+impl fmt::Display for Decimal{
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (integer, fraction) = self.parts();
+        let sign = if self.negative { "-" } else { "" };
+        if fraction.is_empty(){
+            write!(f, "{}{}", sign, integer)
+        }else{
+            write!(f, "{}{}.{}", sign, integer, fraction)
+        }
+    }
+}
+
+/// equal when the values are the same regardless of the scale, ie: 1.50 == 1.5
+impl PartialEq for Decimal{
+    fn eq(&self, other: &Decimal)->bool{
+        let (a, b) = (self.normalized(), other.normalized());
+        a.negative == b.negative && a.digits == b.digits && a.scale == b.scale
+    }
+}
+
 
 /// trait for converting dao to model
 /// sized and clonable
@@ -169,7 +332,8 @@ pub trait IsDao{
     
     /// convert dao to an instance of the corresponding struct of the model
     /// taking into considerating the renamed columns
-    fn from_dao(dao: &Dao)->Self;
+    /// errors when a column is missing or can not be converted to the type of the field
+    fn from_dao(dao: &Dao)->Result<Self, DbError> where Self: Sized;
     
     /// convert from an instance of the struct to a dao representation
     /// to be saved into the database
@@ -252,7 +416,7 @@ impl DaoResult{
     
     /// cast the dao to the specific struct instance
     /// do not include if non nullable parts contains null
-    pub fn cast<T:IsTable+IsDao>(&self)->Vec<T>{
+    pub fn cast<T:IsTable+IsDao>(&self)->Result<Vec<T>, DbError>{
         let table = T::table();
         let non_nulls = table.non_nullable_columns();
         let mut obj = vec![];
@@ -261,20 +425,22 @@ impl DaoResult{
             let mut dao_clone = dao.clone();
             dao_clone.correct_renamed_columns(&renamed_columns);
             if dao_clone.all_has_values(&non_nulls){
-                let p = T::from_dao(&dao_clone);
+                let p = try!(T::from_dao(&dao_clone));
                 obj.push(p);
             }
         }
-        obj
+        Ok(obj)
     }
     
-    pub fn cast_one<T:IsTable+IsDao>(&self)->Option<T>{
-        let mut casted = self.cast::<T>();
+    pub fn cast_one<T:IsTable+IsDao>(&self)->Result<Option<T>, DbError>{
+        let mut casted = try!(self.cast::<T>());
         if casted.len() < 1{
-            return None;
+            return Ok(None);
+        }
+        if casted.len() > 1{
+            return Err(DbError::new(&format!("Expecting only 1 record, but got {}", casted.len())));
         }
-        assert!(casted.len() == 1);
-        Some(casted.remove(0))
+        Ok(Some(casted.remove(0)))
     }
 }
 
@@ -337,33 +503,85 @@ impl Dao{
         }
     }
     /// take the value and remove the content 
+    /// panics when the column is missing or the value can not be converted, see `try_remove`
     pub fn remove<T>(&mut self, column: &str) -> T where T: FromValue{
-        let value = self.values.remove(column).unwrap();
-        FromValue::from_type(value)
+        match self.try_remove(column){
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// take the value and remove the content,
+    /// errors when the column is missing or the value can not be converted
+    pub fn try_remove<T>(&mut self, column: &str) -> Result<T, DbError> where T: FromValue{
+        match self.values.remove(column){
+            Some(value) => Dao::convert(column, value),
+            None => Err(Dao::missing_column(column)),
+        }
     }
 
     /// take the value but not removing the content
+    /// panics when the column is missing or the value can not be converted, see `try_get`
     pub fn get<T>(&self, column: &str) -> T where T: FromValue{
-        let value = self.values.get(column).unwrap();
-        FromValue::from_type(value.clone())
+        match self.try_get(column){
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
     }
+
+    /// take the value but not removing the content,
+    /// errors when the column is missing or the value can not be converted
+    pub fn try_get<T>(&self, column: &str) -> Result<T, DbError> where T: FromValue{
+        match self.values.get(column){
+            Some(value) => Dao::convert(column, value.clone()),
+            None => Err(Dao::missing_column(column)),
+        }
+    }
+
     /// get optional value
+    /// panics when the value can not be converted, see `try_get_opt`
     pub fn get_opt<T>(&self, column: &str) -> Option<T> where T: FromValue{
-        let value = self.values.get(column);
-        if value.is_some(){
-            let v = value.as_ref().unwrap().clone();
-            match v{
-                &Value::Null => None,
-                _ => Some(FromValue::from_type(v.clone()))
-            }
-        }else{
-            None
+        match self.try_get_opt(column){
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// get optional value, None when the column is missing or null,
+    /// errors when the value can not be converted
+    pub fn try_get_opt<T>(&self, column: &str) -> Result<Option<T>, DbError> where T: FromValue{
+        match self.values.get(column){
+            Some(&Value::Null) | None => Ok(None),
+            Some(value) => Dao::convert(column, value.clone()).map(Some),
         }
     }
     
     /// get a reference of the type
+    /// panics when the column is missing, see `try_as_ref`
     pub fn as_ref(&self, column: &str)->&Value{
-        self.values.get(column).unwrap()
+        match self.try_as_ref(column){
+            Ok(value) => value,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// get a reference of the type, errors when the column is missing
+    pub fn try_as_ref(&self, column: &str)->Result<&Value, DbError>{
+        match self.values.get(column){
+            Some(value) => Ok(value),
+            None => Err(Dao::missing_column(column)),
+        }
+    }
+
+    /// convert the value of this column, the error mentions the column
+    fn convert<T>(column: &str, value: Value) -> Result<T, DbError> where T: FromValue{
+        FromValue::from_type(value).map_err(|e: DbError|
+            DbError::with_kind(e.kind().clone(), &format!("Column {}: {}", column, e.description()))
+        )
+    }
+
+    fn missing_column(column: &str)->DbError{
+        DbError::with_kind(DbErrorKind::NotFound, &format!("No such column {}", column))
     }
     
     
@@ -409,6 +627,12 @@ impl ToValue for (){
     }
 }
 
+impl ToValue for Value{
+    fn to_db_type(&self)->Value{
+        self.clone()
+    }
+}
+
 impl ToValue for bool{
     fn to_db_type(&self)->Value{
         Value::Bool(self.clone())
@@ -474,6 +698,143 @@ impl ToValue for f64{
     }
 }
 
+impl ToValue for Decimal{
+    fn to_db_type(&self)->Value{
+        Value::Decimal(self.clone())
+    }
+}
+
+impl ToValue for Json{
+    fn to_db_type(&self)->Value{
+        Value::Json(self.clone())
+    }
+}
+
+fn array_value<T: ToValue>(elements:&Vec<T>)->Value{
+    Value::Array(elements.iter().map(|x| x.to_db_type()).collect())
+}
+
+/// bytes are not an array, they are stored in bytea/blob columns
+impl ToValue for Vec<u8>{
+    fn to_db_type(&self)->Value{
+        Value::VecU8(self.clone())
+    }
+}
+
+impl ToValue for Vec<bool>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<i8>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<i16>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<i32>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<i64>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<u16>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<u32>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<u64>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<f32>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<f64>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<Decimal>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<Json>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<String>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<Uuid>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<DateTime<UTC>>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<NaiveDate>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<NaiveTime>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl ToValue for Vec<NaiveDateTime>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
+impl <'a>ToValue for Vec<&'a str>{
+    fn to_db_type(&self)->Value{
+        array_value(self)
+    }
+}
+
 impl <'a>ToValue for &'a str{
     fn to_db_type(&self)->Value{
         Value::String(self.to_string())
@@ -515,164 +876,355 @@ impl ToValue for NaiveDateTime{
     }
 }
 
+/// convert the generic Value into the rust type,
+/// errors when the value can not be converted without loss.
 ///
-///
-///
-///
-///
-///
-///
-///
-///
-///
-///
-///
-///
+/// Some platforms returns the values as String (sqlite, mysql)
+/// or in a narrower type (postgresql int4), so the conversion is lenient as long as it is lossless:
+/// integers are widened or range checked, floats accept integers that are represented exactly,
+/// String is parsed into numbers, Uuid and dates, and integer 0 and 1 is accepted as bool.
 pub trait FromValue{
-    fn from_type(ty:Value)->Self;
+    fn from_type(ty:Value)->Result<Self, DbError> where Self: Sized;
+}
+
+/// the error when the value can not be converted to the expected type
+fn conversion_error(expected: &str, ty: &Value)->DbError{
+    DbError::with_kind(DbErrorKind::TypeConversion,
+        &format!("expecting {}, but the value is {}", expected, ty.variant_name()))
+}
+
+fn out_of_range_error(expected: &str, ty: &Value)->DbError{
+    DbError::with_kind(DbErrorKind::TypeConversion,
+        &format!("expecting {}, but the value {} is out of range", expected, ty))
+}
+
+fn parse_error(expected: &str, s: &str)->DbError{
+    DbError::with_kind(DbErrorKind::TypeConversion,
+        &format!("expecting {}, but the String '{}' can not be parsed", expected, s))
+}
+
+/// convert any integer value or numeric String into i64, within the range of min and max
+fn signed_integer(expected: &str, ty: &Value, min: i64, max: i64)->Result<i64, DbError>{
+    let value = match *ty{
+        Value::I8(x) => x as i64,
+        Value::I16(x) => x as i64,
+        Value::I32(x) => x as i64,
+        Value::I64(x) => x,
+        Value::U8(x) => x as i64,
+        Value::U16(x) => x as i64,
+        Value::U32(x) => x as i64,
+        Value::U64(x) => {
+            if x > i64::max_value() as u64 {
+                return Err(out_of_range_error(expected, ty));
+            }
+            x as i64
+        },
+        Value::String(ref s) => match s.trim().parse::<i64>(){
+            Ok(x) => x,
+            Err(_) => return Err(parse_error(expected, s)),
+        },
+        Value::Decimal(ref x) => match x.to_i64(){
+            Some(x) => x,
+            None => return Err(out_of_range_error(expected, ty)),
+        },
+        _ => return Err(conversion_error(expected, ty)),
+    };
+    if value < min || value > max {
+        return Err(out_of_range_error(expected, ty));
+    }
+    Ok(value)
+}
+
+/// convert any integer value or numeric String into u64, within the range of 0 and max
+fn unsigned_integer(expected: &str, ty: &Value, max: u64)->Result<u64, DbError>{
+    let value = match *ty{
+        Value::U8(x) => x as u64,
+        Value::U16(x) => x as u64,
+        Value::U32(x) => x as u64,
+        Value::U64(x) => x,
+        Value::I8(_) | Value::I16(_) | Value::I32(_) | Value::I64(_) | Value::Decimal(_) => {
+            let x = try!(signed_integer(expected, ty, 0, i64::max_value()));
+            x as u64
+        },
+        Value::String(ref s) => match s.trim().parse::<u64>(){
+            Ok(x) => x,
+            Err(_) => return Err(parse_error(expected, s)),
+        },
+        _ => return Err(conversion_error(expected, ty)),
+    };
+    if value > max {
+        return Err(out_of_range_error(expected, ty));
+    }
+    Ok(value)
+}
+
+/// convert any number or numeric String into f64, integers should be represented exactly
+fn float(expected: &str, ty: &Value)->Result<f64, DbError>{
+    match *ty{
+        Value::F32(x) => Ok(x as f64),
+        Value::F64(x) => Ok(x),
+        Value::U64(x) => {
+            let f = x as f64;
+            if f as u64 == x && f < u64::max_value() as f64 { Ok(f) } else { Err(out_of_range_error(expected, ty)) }
+        },
+        Value::I8(_) | Value::I16(_) | Value::I32(_) | Value::I64(_)
+        | Value::U8(_) | Value::U16(_) | Value::U32(_) => {
+            let x = try!(signed_integer(expected, ty, i64::min_value(), i64::max_value()));
+            let f = x as f64;
+            if f as i64 == x && f < i64::max_value() as f64 { Ok(f) } else { Err(out_of_range_error(expected, ty)) }
+        },
+        Value::String(ref s) => match s.trim().parse::<f64>(){
+            Ok(x) => Ok(x),
+            Err(_) => Err(parse_error(expected, s)),
+        },
+        // only when the decimal has an exact representation as f64, ie: 9.5 but not 0.1234567890123456789
+        Value::Decimal(ref x) => {
+            let f = x.to_f64();
+            match Decimal::from_str(&format!("{}", f)){
+                Ok(ref d) if d == x => Ok(f),
+                _ => Err(out_of_range_error(expected, ty)),
+            }
+        },
+        _ => Err(conversion_error(expected, ty)),
+    }
+}
+
+/// accepts the date time formats returned by the databases,
+/// with the date and time separated by either space or `T`
+fn parse_naive_datetime(s: &str)->Option<NaiveDateTime>{
+    let s = s.trim();
+    for format in &["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]{
+        if let Ok(x) = NaiveDateTime::parse_from_str(s, format){
+            return Some(x);
+        }
+    }
+    None
 }
 
 impl FromValue for bool{
-    fn from_type(ty:Value)->Self{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::Bool(x) => x,
-            _ => panic!("error!"),
+            Value::Bool(x) => Ok(x),
+            Value::String(ref s) => match &*s.trim().to_lowercase(){
+                "true" | "t" | "1" => Ok(true),
+                "false" | "f" | "0" => Ok(false),
+                _ => Err(parse_error("bool", s)),
+            },
+            // sqlite stores booleans as integer 0 and 1
+            _ => {
+                let x = try!(signed_integer("bool", &ty, 0, 1));
+                Ok(x == 1)
+            },
         }
     }
 }
 
 impl FromValue for i8{
-    fn from_type(ty:Value)->Self{
-        match ty{
-            Value::I8(x) => x,
-            _ => panic!("error!"),
-        }
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        let x = try!(signed_integer("i8", &ty, i8::min_value() as i64, i8::max_value() as i64));
+        Ok(x as i8)
     }
 }
 impl FromValue for i16{
-    fn from_type(ty:Value)->Self{
-        match ty{
-            Value::I16(x) => x,
-            _ => panic!("error!"),
-        }
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        let x = try!(signed_integer("i16", &ty, i16::min_value() as i64, i16::max_value() as i64));
+        Ok(x as i16)
     }
 }
 impl FromValue for i32{
-    fn from_type(ty:Value)->Self{
-        match ty{
-            Value::I32(x) => x,
-            _ => panic!("error!"),
-        }
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        let x = try!(signed_integer("i32", &ty, i32::min_value() as i64, i32::max_value() as i64));
+        Ok(x as i32)
     }
 }
 impl FromValue for i64{
-    fn from_type(ty:Value)->Self{
-        match ty{
-            Value::I64(x) => x,
-            _ => panic!("error!"),
-        }
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        signed_integer("i64", &ty, i64::min_value(), i64::max_value())
     }
 }
 impl FromValue for u8{
-    fn from_type(ty:Value)->Self{
-        match ty{
-            Value::U8(x) => x,
-            _ => panic!("error!"),
-        }
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        let x = try!(unsigned_integer("u8", &ty, u8::max_value() as u64));
+        Ok(x as u8)
     }
 }
 impl FromValue for u16{
-    fn from_type(ty:Value)->Self{
-        match ty{
-            Value::U16(x) => x,
-            _ => panic!("error!"),
-        }
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        let x = try!(unsigned_integer("u16", &ty, u16::max_value() as u64));
+        Ok(x as u16)
     }
 }
 impl FromValue for u32{
-    fn from_type(ty:Value)->Self{
-        match ty{
-            Value::U32(x) => x,
-            _ => panic!("error!"),
-        }
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        let x = try!(unsigned_integer("u32", &ty, u32::max_value() as u64));
+        Ok(x as u32)
     }
 }
 impl FromValue for u64{
-    fn from_type(ty:Value)->Self{
-        match ty{
-            Value::U64(x) => x,
-            _ => panic!("error!"),
-        }
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        unsigned_integer("u64", &ty, u64::max_value())
     }
 }
 
 impl FromValue for f32{
-    fn from_type(ty:Value)->Self{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::F32(x) => x,
-            _ => panic!("error!"),
+            Value::F32(x) => Ok(x),
+            _ => {
+                let x = try!(float("f32", &ty));
+                // only when it can be represented exactly in f32
+                if x as f32 as f64 == x { Ok(x as f32) } else { Err(out_of_range_error("f32", &ty)) }
+            }
         }
     }
 }
 impl FromValue for f64{
-    fn from_type(ty:Value)->Self{
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        float("f64", &ty)
+    }
+}
+
+impl FromValue for String{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::F64(x) => x,
-            _ => panic!("error! {:?}",ty),
+            Value::String(x) => Ok(x),
+            Value::Json(ref x) => Ok(x.to_string()),
+            Value::Decimal(ref x) => Ok(x.to_string()),
+            _ => Err(conversion_error("String", &ty)),
         }
     }
 }
 
-impl FromValue for String{
-    fn from_type(ty:Value)->Self{
+impl FromValue for Decimal{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::String(x) => x,
-            _ => panic!("error! {:?}", ty),
+            Value::Decimal(x) => Ok(x),
+            Value::String(ref s) => Decimal::from_str(s),
+            Value::I8(_) | Value::I16(_) | Value::I32(_) | Value::I64(_)
+            | Value::U8(_) | Value::U16(_) | Value::U32(_) => {
+                let x = try!(signed_integer("Decimal", &ty, i64::min_value(), i64::max_value()));
+                Ok(Decimal::new(x, 0))
+            },
+            Value::U64(x) => Decimal::from_str(&format!("{}", x)),
+            // the shortest representation which converts back to the same float
+            Value::F32(x) if x.is_finite() => Decimal::from_str(&format!("{}", x)),
+            Value::F64(x) if x.is_finite() => Decimal::from_str(&format!("{}", x)),
+            _ => Err(conversion_error("Decimal", &ty)),
+        }
+    }
+}
+
+/// each of the elements is converted, bytes are accepted as an array of u8
+impl <T: FromValue>FromValue for Vec<T>{
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        let elements = match ty{
+            Value::Array(x) => x,
+            Value::VecU8(x) => x.into_iter().map(Value::U8).collect(),
+            _ => return Err(conversion_error("Array", &ty)),
+        };
+        let mut converted = vec![];
+        for (i, element) in elements.into_iter().enumerate(){
+            match T::from_type(element){
+                Ok(x) => converted.push(x),
+                Err(e) => return Err(DbError::with_kind(e.kind().clone(), &format!("Element {}: {}", i, e.description()))),
+            }
+        }
+        Ok(converted)
+    }
+}
+
+/// json is parsed from the text, for the platforms which stores json as TEXT
+impl FromValue for Json{
+    fn from_type(ty:Value)->Result<Self, DbError>{
+        match ty{
+            Value::Json(x) => Ok(x),
+            Value::Object(_) => Ok(ty.to_json()),
+            Value::String(ref s) => match Json::from_str(s){
+                Ok(x) => Ok(x),
+                Err(_) => Err(parse_error("Json", s)),
+            },
+            Value::VecU8(ref x) => match String::from_utf8(x.clone()).ok().and_then(|s| Json::from_str(&s).ok()){
+                Some(x) => Ok(x),
+                None => Err(conversion_error("Json", &ty)),
+            },
+            _ => Err(conversion_error("Json", &ty)),
         }
     }
 }
 
 impl FromValue for Uuid{
-    fn from_type(ty:Value)->Self{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::Uuid(x) => x,
-            _ => panic!("error!"),
+            Value::Uuid(x) => Ok(x),
+            Value::String(ref s) => match Uuid::parse_str(s.trim()){
+                Ok(x) => Ok(x),
+                Err(_) => Err(parse_error("Uuid", s)),
+            },
+            // ie: uuid stored in mysql BINARY(16)
+            Value::VecU8(ref bytes) if bytes.len() == 16 => match Uuid::from_bytes(bytes){
+                Ok(x) => Ok(x),
+                Err(_) => Err(conversion_error("Uuid", &ty)),
+            },
+            _ => Err(conversion_error("Uuid", &ty)),
         }
     }
 }
 
 impl FromValue for DateTime<UTC>{
-    fn from_type(ty:Value)->Self{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::DateTime(x) => x,
-            _ => panic!("error!"),
+            Value::DateTime(x) => Ok(x),
+            // the databases without timezone stores the date time in UTC
+            Value::NaiveDateTime(x) => Ok(DateTime::from_utc(x, UTC)),
+            Value::String(ref s) => {
+                if let Ok(x) = DateTime::parse_from_rfc3339(s.trim()){
+                    return Ok(x.with_timezone(&UTC));
+                }
+                match parse_naive_datetime(s){
+                    Some(x) => Ok(DateTime::from_utc(x, UTC)),
+                    None => Err(parse_error("DateTime<UTC>", s)),
+                }
+            },
+            _ => Err(conversion_error("DateTime<UTC>", &ty)),
         }
     }
 }
 
 impl FromValue for NaiveTime{
-    fn from_type(ty:Value)->Self{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::NaiveTime(x) => x,
-            _ => panic!("error!"),
+            Value::NaiveTime(x) => Ok(x),
+            Value::String(ref s) => match NaiveTime::parse_from_str(s.trim(), "%H:%M:%S%.f"){
+                Ok(x) => Ok(x),
+                Err(_) => Err(parse_error("NaiveTime", s)),
+            },
+            _ => Err(conversion_error("NaiveTime", &ty)),
         }
     }
 }
 
 impl FromValue for NaiveDate{
-    fn from_type(ty:Value)->Self{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::NaiveDate(x) => x,
-            _ => panic!("error!"),
+            Value::NaiveDate(x) => Ok(x),
+            Value::String(ref s) => match NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d"){
+                Ok(x) => Ok(x),
+                Err(_) => Err(parse_error("NaiveDate", s)),
+            },
+            _ => Err(conversion_error("NaiveDate", &ty)),
         }
     }
 }
 
 impl FromValue for NaiveDateTime{
-    fn from_type(ty:Value)->Self{
+    fn from_type(ty:Value)->Result<Self, DbError>{
         match ty{
-            Value::NaiveDateTime(x) => x,
-            _ => panic!("error!"),
+            Value::NaiveDateTime(x) => Ok(x),
+            Value::String(ref s) => match parse_naive_datetime(s){
+                Some(x) => Ok(x),
+                None => Err(parse_error("NaiveDateTime", s)),
+            },
+            _ => Err(conversion_error("NaiveDateTime", &ty)),
         }
     }
 }
@@ -716,3 +1268,118 @@ fn test_json(){
     println!("expected: {}", expected);
     println!("actual: {}",actual);
 }
+
+#[test]
+fn test_try_get(){
+    let mut dao = Dao::new();
+    dao.set("name", &"lee");
+    dao.set_null("description");
+    let name:Result<String, DbError> = dao.try_get("name");
+    assert_eq!(name.unwrap(), "lee");
+    let description:Option<String> = dao.try_get_opt("description").unwrap();
+    assert_eq!(description, None);
+    let missing:Result<String, DbError> = dao.try_get("missing");
+    assert_eq!(missing.unwrap_err().kind(), &DbErrorKind::NotFound);
+    dao.set("age", &20i64);
+    let age:Result<NaiveDate, DbError> = dao.try_get("age");
+    let err = age.unwrap_err();
+    assert_eq!(err.kind(), &DbErrorKind::TypeConversion);
+    assert_eq!(err.description(), "Column age: expecting NaiveDate, but the value is I64");
+}
+
+#[test]
+fn test_lossless_coercion(){
+    let mut dao = Dao::new();
+    dao.set("int4", &10i32);
+    dao.set("big", &300i64);
+    dao.set("negative", &-1i64);
+    dao.set("flag", &1i64);
+    dao.set("count", &"42");
+    dao.set("price", &"9.5");
+    dao.set("product_id", &"f7521093-734d-488a-9f60-fc9f11f7e750");
+    dao.set("created", &"2016-01-30 12:20:01");
+    dao.set("birthday", &"2016-01-30");
+    let int4:i64 = dao.get("int4");
+    assert_eq!(int4, 10);
+    let int4:f64 = dao.get("int4");
+    assert_eq!(int4, 10.0);
+    let big:i16 = dao.get("big");
+    assert_eq!(big, 300);
+    assert!(dao.try_get::<i8>("big").is_err());
+    assert!(dao.try_get::<u32>("negative").is_err());
+    let flag:bool = dao.get("flag");
+    assert_eq!(flag, true);
+    assert!(dao.try_get::<bool>("big").is_err());
+    let count:u32 = dao.get("count");
+    assert_eq!(count, 42);
+    let price:f64 = dao.get("price");
+    assert_eq!(price, 9.5);
+    let product_id:Uuid = dao.get("product_id");
+    assert_eq!(product_id.to_string(), "f7521093-734d-488a-9f60-fc9f11f7e750");
+    let created:NaiveDateTime = dao.get("created");
+    assert_eq!(created, NaiveDate::from_ymd(2016, 1, 30).and_hms(12, 20, 1));
+    let created:DateTime<UTC> = dao.get("created");
+    assert_eq!(created.naive_utc(), NaiveDate::from_ymd(2016, 1, 30).and_hms(12, 20, 1));
+    let birthday:NaiveDate = dao.get("birthday");
+    assert_eq!(birthday, NaiveDate::from_ymd(2016, 1, 30));
+    assert!(dao.try_get::<Uuid>("price").is_err());
+}
+
+#[test]
+fn test_decimal(){
+    let price = Decimal::from_str("123.450").unwrap();
+    assert_eq!(price.to_string(), "123.450");
+    assert_eq!(price.scale(), 3);
+    assert_eq!(price, Decimal::new(12345, 2));
+    assert_eq!(Decimal::from_str("-0.05").unwrap().to_string(), "-0.05");
+    assert_eq!(Decimal::from_str(".5").unwrap().to_string(), "0.5");
+    assert!(Decimal::from_str("12a.5").is_err());
+    assert_eq!(Value::Decimal(price.clone()).to_json(), Json::String("123.450".to_string()));
+    let mut dao = Dao::new();
+    dao.set("price", &price);
+    dao.set("cents", &"0.1234567890123456789");
+    let price:f64 = dao.get("price");
+    assert_eq!(price, 123.45);
+    let cents:Decimal = dao.get("cents");
+    assert_eq!(cents.to_string(), "0.1234567890123456789");
+    dao.set("cents", &cents);
+    assert!(dao.try_get::<f64>("cents").is_err());
+    assert!(dao.try_get::<i64>("price").is_err());
+}
+
+#[test]
+fn test_json_value(){
+    let info = Json::from_str("{\"color\":\"red\",\"weight\":4}").unwrap();
+    let mut dao = Dao::new();
+    dao.set("info", &info);
+    dao.set("text", &"{\"color\":\"red\",\"weight\":4}");
+    let value:Json = dao.get("info");
+    assert_eq!(value, info);
+    let text:Json = dao.get("text");
+    assert_eq!(text, info);
+    dao.set("text", &"{color}");
+    assert!(dao.try_get::<Json>("text").is_err());
+}
+
+#[test]
+fn test_array_value(){
+    let mut dao = Dao::new();
+    dao.set("tags", &vec!["gpu".to_string(), "nvidia".to_string()]);
+    dao.set("ids", &vec![1i32, 2, 3]);
+    assert_eq!(dao.get_value("tags"), Value::Array(vec![Value::String("gpu".to_string()), Value::String("nvidia".to_string())]));
+    let tags:Vec<String> = dao.get("tags");
+    assert_eq!(tags, vec!["gpu".to_string(), "nvidia".to_string()]);
+    let ids:Vec<i64> = dao.get("ids");
+    assert_eq!(ids, vec![1, 2, 3]);
+    let err = dao.try_get::<Vec<Uuid>>("ids").unwrap_err();
+    assert_eq!(err.description(), "Column ids: Element 0: expecting Uuid, but the value is I32");
+}
+
+#[test]
+fn test_bytes_value(){
+    let mut dao = Dao::new();
+    dao.set("photo", &vec![0u8, 1, 255]);
+    assert_eq!(dao.get_value("photo"), Value::VecU8(vec![0, 1, 255]));
+    let photo:Vec<u8> = dao.get("photo");
+    assert_eq!(photo, vec![0, 1, 255]);
+}
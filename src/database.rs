@@ -2,12 +2,13 @@ use query::Query;
 use table::Table;
 use dao::{Dao,DaoResult, Value};
 use writer::SqlFrag;
-use query::{Connector, Equality, Operand, Field};
-use query::{Direction, Modifier, JoinType};
+use query::{Equality, Operand, Field, ColumnName, JsonPath, JsonSegment};
+use query::{Direction, NullsWhere, Order, Modifier, JoinType};
 use query::{Filter, Condition};
 use query::SqlType;
 use std::error::Error;
 use std::fmt;
+use std::cell::Cell;
 
 
 /// SqlOption, contains the info about the features and quirks of underlying database
@@ -28,19 +29,125 @@ pub enum SqlOption{
     /// wheter the returned rows in a query included Meta columns for easy extraction of records
     /// (postgres returns this), sqlite does not return meta columns, so you have to extract it by index yourself.
     ReturnMetaColumns,
+    /// supports DISTINCT ON (columns) (postgresql)
+    SupportsDistinctOn,
+    /// upsert using ON CONFLICT (columns) DO UPDATE (postgresql, sqlite 3.24+)
+    SupportsOnConflict,
+    /// upsert using ON DUPLICATE KEY UPDATE, the conflict is detected by any unique constraint (mysql)
+    SupportsOnDuplicateKey,
+    /// array columns and the array operators = ANY, @> and && (postgresql)
+    SupportsArrays,
+    /// the json operators ->, ->>, @> and ? (postgresql)
+    SupportsJsonOperators,
+    /// extracting values from json text with json_extract (sqlite with the json1 extension)
+    SupportsJsonExtract,
+    /// case insensitive ILIKE, emulated with lower() when not supported (postgresql)
+    SupportsILike,
+    /// POSIX regular expression operators ~ and ~* (postgresql)
+    SupportsRegex,
+    /// IS [NOT] DISTINCT FROM (postgresql)
+    SupportsDistinctFrom,
+    /// IS and IS NOT compares NULL as a value, for IS [NOT] DISTINCT FROM (sqlite)
+    UsesIsForDistinctFrom,
+    /// the NULL-safe equal operator <=>, for IS [NOT] DISTINCT FROM (mysql)
+    UsesNullSafeEqual,
+    /// NULLS FIRST and NULLS LAST in the ORDER BY, emulated with CASE when not supported (postgresql)
+    SupportsNullsOrder,
+}
+
+/// the kind of database error, so the caller can react accordingly
+/// ie: a conflict on a unique violation, not found when there is no record
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum DbErrorKind{
+    /// unable to connect to the database, or the connection is lost
+    Connection,
+    /// there is no record, when one is expected
+    NotFound,
+    /// a record with the same values on the unique or primary columns already exists
+    UniqueViolation{constraint: Option<String>},
+    /// the referred record does not exist, or the record is still being referred to
+    ForeignKeyViolation{constraint: Option<String>},
+    /// a null value on a column that is not nullable
+    NotNullViolation{column: Option<String>},
+    /// the value can not be converted from or to the database type
+    TypeConversion,
+    /// the sql statement is invalid
+    Syntax,
+    /// any other error
+    Other,
+}
+
+impl DbErrorKind{
+
+    /// the kind of error from the SQLSTATE error code (postgresql, mysql)
+    /// http://www.postgresql.org/docs/current/static/errcodes-appendix.html
+    pub fn from_sqlstate(code: &str, constraint: Option<String>, column: Option<String>)->Self{
+        match code{
+            "23505" => DbErrorKind::UniqueViolation{constraint: constraint},
+            "23503" => DbErrorKind::ForeignKeyViolation{constraint: constraint},
+            "23502" => DbErrorKind::NotNullViolation{column: column},
+            "42601" => DbErrorKind::Syntax,
+            _ if code.starts_with("08") => DbErrorKind::Connection,
+            _ if code.starts_with("22") => DbErrorKind::TypeConversion,
+            _ => DbErrorKind::Other,
+        }
+    }
+
+    /// a short description of the kind of error, the details are in the cause
+    pub fn description(&self)->&str{
+        match *self{
+            DbErrorKind::Connection => "database connection error",
+            DbErrorKind::NotFound => "record not found",
+            DbErrorKind::UniqueViolation{..} => "unique constraint violation",
+            DbErrorKind::ForeignKeyViolation{..} => "foreign key constraint violation",
+            DbErrorKind::NotNullViolation{..} => "not null constraint violation",
+            DbErrorKind::TypeConversion => "type conversion error",
+            DbErrorKind::Syntax => "sql syntax error",
+            DbErrorKind::Other => "database error",
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct DbError{
+    kind: DbErrorKind,
     description: String,
-    cause: Option<String>,
+    /// the original error from the database driver
+    cause: Option<Box<Error + Send + Sync>>,
 }
 
 /// rough implementation of Database errors
 impl DbError{
     
     pub fn new(description: &str)->Self{
-        DbError{description: description.to_string(), cause: None}
+        DbError::with_kind(DbErrorKind::Other, description)
+    }
+
+    pub fn with_kind(kind: DbErrorKind, description: &str)->Self{
+        DbError{kind: kind, description: description.to_string(), cause: None}
+    }
+
+    /// keep the original error from the database driver as the cause
+    pub fn with_cause<E>(kind: DbErrorKind, description: &str, cause: E)->Self
+        where E: Error + Send + Sync + 'static{
+        DbError{kind: kind, description: description.to_string(), cause: Some(Box::new(cause))}
+    }
+
+    /// the error from the database driver, described by the kind of the error
+    pub fn from_driver<E>(kind: DbErrorKind, cause: E)->Self
+        where E: Error + Send + Sync + 'static{
+        let description = kind.description().to_string();
+        DbError::with_cause(kind, &description, cause)
+    }
+
+    pub fn not_found(description: &str)->Self{
+        DbError::with_kind(DbErrorKind::NotFound, description)
+    }
+
+    pub fn kind(&self)->&DbErrorKind{
+        &self.kind
     }
 }
 
@@ -51,13 +158,163 @@ impl Error for DbError{
      }
 
     fn cause(&self) -> Option<&Error> { 
-        None
+        match self.cause{
+            Some(ref cause) => Some(&**cause),
+            None => None,
+        }
     }
 }
 
 impl fmt::Display for DbError {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{}", self.description())
+        match self.cause{
+            Some(ref cause) => write!(fmt, "{}, {}", self.description(), cause),
+            None => write!(fmt, "{}", self.description()),
+        }
+    }
+}
+
+/// how tight a filter binds when rendered, used to only put parenthesis where it is needed
+/// a group of only 1 filter binds the same as the filter itself
+fn filter_precedence(filter: &Filter)->u8{
+    match *filter{
+        Filter::Any(ref filters) | Filter::All(ref filters) if filters.len() == 1 => {
+            filter_precedence(&filters[0])
+        },
+        Filter::Any(ref filters) if !filters.is_empty() => 1,
+        Filter::All(ref filters) if !filters.is_empty() => 2,
+        Filter::Not(_) => 3,
+        _ => 4,
+    }
+}
+
+/// the number of transactions in progress on a connection,
+/// it only changes when the statement which begins or ends the transaction succeeds
+/// so the savepoint names keep matching the ones in the database
+#[derive(Debug)]
+pub struct TransactionDepth{
+    depth: Cell<usize>,
+}
+
+impl TransactionDepth{
+
+    pub fn new()->Self{
+        TransactionDepth{depth: Cell::new(0)}
+    }
+
+    pub fn get(&self)->usize{
+        self.depth.get()
+    }
+
+    /// begin a transaction, or a savepoint when nested, with `execute` running the sql
+    pub fn begin<F>(&self, execute: F)->Result<(), DbError>
+        where F: FnOnce(&str)->Result<(), DbError>{
+        let depth = self.depth.get();
+        try!(execute(&begin_sql(depth)));
+        self.depth.set(depth + 1);
+        Ok(())
+    }
+
+    /// commit the innermost transaction, it is still in progress when the commit fails
+    pub fn commit<F>(&self, execute: F)->Result<(), DbError>
+        where F: FnOnce(&str)->Result<(), DbError>{
+        let depth = self.depth.get();
+        if depth == 0{
+            return Err(DbError::new("There is no transaction to commit"));
+        }
+        try!(execute(&commit_sql(depth - 1)));
+        self.depth.set(depth - 1);
+        Ok(())
+    }
+
+    /// rollback the innermost transaction, a savepoint is rolled back to and then released,
+    /// it is still in progress when either fails.
+    /// the outermost transaction is over even when the rollback fails
+    pub fn rollback<F>(&self, mut execute: F)->Result<(), DbError>
+        where F: FnMut(&str)->Result<(), DbError>{
+        let depth = self.depth.get();
+        if depth == 0{
+            return Err(DbError::new("There is no transaction to rollback"));
+        }
+        if depth == 1{
+            self.depth.set(0);
+            return execute(&rollback_sql(0));
+        }
+        try!(execute(&rollback_sql(depth - 1)));
+        try!(execute(&commit_sql(depth - 1)));
+        self.depth.set(depth - 1);
+        Ok(())
+    }
+}
+
+/// the statement to begin a transaction when there are already `depth` transactions in progress,
+/// a nested transaction is a savepoint
+pub fn begin_sql(depth: usize)->String{
+    if depth == 0 { "BEGIN".to_string() } else { format!("SAVEPOINT sp_{}", depth) }
+}
+
+/// the statement to commit the innermost transaction,
+/// `depth` is the number of transactions still in progress afterwards
+pub fn commit_sql(depth: usize)->String{
+    if depth == 0 { "COMMIT".to_string() } else { format!("RELEASE SAVEPOINT sp_{}", depth) }
+}
+
+/// the statement to rollback the innermost transaction, a nested transaction only rolls back to its savepoint
+/// which is then released with `commit_sql`,
+/// `depth` is the number of transactions still in progress afterwards
+pub fn rollback_sql(depth: usize)->String{
+    if depth == 0 { "ROLLBACK".to_string() } else { format!("ROLLBACK TO SAVEPOINT sp_{}", depth) }
+}
+
+/// insert the rows of one statement of a bulk insert
+fn insert_chunk<D: Database + ?Sized>(db: &D, chunk: &Query, with_return: bool)->Result<Vec<Dao>, DbError>{
+    let sql_frag = try!(db.build_insert(chunk));
+    if with_return{
+        db.execute_sql_with_return(&sql_frag.sql, &sql_frag.params)
+    }else{
+        try!(db.execute_sql(&sql_frag.sql, &sql_frag.params));
+        Ok(vec![])
+    }
+}
+
+/// A database transaction which is rolled back when dropped without being committed,
+/// such as when returning early from an error
+pub struct Transaction<'a>{
+    db: &'a Database,
+    finished: bool,
+}
+
+impl <'a>Transaction<'a>{
+
+    /// begin a transaction on this database connection
+    pub fn new(db: &'a Database)->Result<Self, DbError>{
+        try!(db.begin());
+        Ok(Transaction{db: db, finished: false})
+    }
+
+    /// the database connection this transaction is running on
+    pub fn as_ref(&self)->&Database{
+        self.db
+    }
+
+    pub fn commit(mut self)->Result<(), DbError>{
+        self.finished = true;
+        self.db.commit()
+    }
+
+    pub fn rollback(mut self)->Result<(), DbError>{
+        self.finished = true;
+        self.db.rollback()
+    }
+}
+
+impl <'a>Drop for Transaction<'a>{
+
+    fn drop(&mut self){
+        if !self.finished{
+            // there is no one to report the error to when dropping
+            let _ = self.db.rollback();
+        }
     }
 }
 
@@ -73,18 +330,21 @@ pub trait Database{
 
     /// return the version of the database
     /// lower version of database has fewer supported features
-    fn version(&self)->String;
+    fn version(&self)->Result<String, DbError>;
     
-    /// begin database transaction
-    fn begin(&self);
+    /// begin database transaction,
+    /// when there is already a transaction in progress, a savepoint is created instead
+    fn begin(&self)->Result<(), DbError>;
 
     /// commit database transaction
-    fn commit(&self);
+    fn commit(&self)->Result<(), DbError>;
 
-    /// rollback data changes executed prior to calling the begin method
-    fn rollback(&self);
+    /// rollback data changes executed prior to calling the begin method,
+    /// a nested transaction is only rolled back to its savepoint
+    fn rollback(&self)->Result<(), DbError>;
 
-    /// determine if this transaction has been committed or rolledback
+    /// determine if there is a transaction in progress,
+    /// one which has begun but not yet committed or rolledback
     fn is_transacted(&self)->bool;
 
     /// determine if the database connection closed
@@ -94,13 +354,13 @@ pub trait Database{
     fn is_connected(&self)->bool;
 
     /// close the database connection
-    fn close(&self);
+    fn close(&self)->Result<(), DbError>;
 
     /// determine if the database connection is still valid
     fn is_valid(&self)->bool;
 
     /// reset the database connection
-    fn reset(&self);
+    fn reset(&self)->Result<(), DbError>;
 
     /// select
     /// returns an array to the qualified records
@@ -112,22 +372,59 @@ pub trait Database{
     /// insert an object, returns the inserted Dao value
     /// including the value generated via the defaults
     fn insert(&self, query:&Query)->Result<Dao, DbError>{
-        let sql_frag = self.build_insert(query);
+        let sql_frag = try!(self.build_insert(query));
         self.execute_sql_with_one_return(&sql_frag.sql, &sql_frag.params)
     }
 
+    /// bulk insert, the rows in the query values are inserted with as few statements
+    /// as the parameter limit of the database allows.
+    /// returns the inserted records when the database supports the returning clause, otherwise empty.
+    /// the statements run in a transaction (a savepoint when already in one), so either all rows are inserted or none
+    fn insert_many(&self, query:&Query)->Result<Vec<Dao>, DbError>{
+        let with_return = !query.enumerated_returns.is_empty()
+            && self.sql_options().contains(&SqlOption::SupportsReturningClause);
+        let chunks = try!(query.split_values(self.max_parameters()));
+        if chunks.len() == 1{
+            return insert_chunk(self, &chunks[0], with_return);
+        }
+        try!(self.begin());
+        let mut inserted = vec![];
+        for chunk in &chunks{
+            match insert_chunk(self, chunk, with_return){
+                Ok(daos) => inserted.extend(daos),
+                Err(e) => {
+                    let _ = self.rollback();
+                    return Err(e);
+                }
+            }
+        }
+        try!(self.commit());
+        Ok(inserted)
+    }
+
     /// update
-    /// returns the updated Dao
-    fn update(&self,query:&Query)->Dao;
+    /// returns the updated Dao using the returning clause,
+    /// use `execute` on the databases which has no returning clause, it returns the number of updated records
+    fn update(&self, query:&Query)->Result<Dao, DbError>{
+        if query.enumerated_returns.is_empty()
+            || !self.sql_options().contains(&SqlOption::SupportsReturningClause){
+            return Err(DbError::new("Returning the updated record requires the returning clause"));
+        }
+        let sql_frag = try!(self.build_update(query));
+        self.execute_sql_with_one_return(&sql_frag.sql, &sql_frag.params)
+    }
 
     /// delete records
     /// returns the number of deleted records
-    fn delete(&self, query:&Query)->Result<usize, String>;
+    fn delete(&self, query:&Query)->Result<usize, DbError>{
+        let sql_frag = try!(self.build_delete(query));
+        self.execute_sql(&sql_frag.sql, &sql_frag.params)
+    }
 
     /// execute query with return dao,
     /// use the enumerated column for data extraction when db doesn't support returning the records column names
     fn execute_with_return(&self, query:&Query)->Result<DaoResult, DbError>{
-        let sql_frag = &self.build_query(query);
+        let sql_frag = &try!(self.build_query(query));
         let result = self.execute_sql_with_return(&sql_frag.sql, &sql_frag.params);
         match result{
             Ok(result) => {
@@ -147,13 +444,13 @@ pub trait Database{
 
     /// execute query with 1 return dao
     fn execute_with_one_return(&self, query:&Query)->Result<Dao, DbError>{
-        let sql_frag = &self.build_query(query);
+        let sql_frag = &try!(self.build_query(query));
         self.execute_sql_with_one_return(&sql_frag.sql, &sql_frag.params)
     }
     
     /// execute query with no return dao
     fn execute(&self, query:&Query)->Result<usize, DbError>{
-        let sql_frag = &self.build_query(query);
+        let sql_frag = &try!(self.build_query(query));
         self.execute_sql(&sql_frag.sql, &sql_frag.params)
     }
 
@@ -161,17 +458,11 @@ pub trait Database{
     fn execute_sql_with_return(&self, sql:&str, params:&Vec<Value>)->Result<Vec<Dao>, DbError>;
     
     fn execute_sql_with_one_return(&self, sql:&str, params:&Vec<Value>)->Result<Dao, DbError>{
-        let dao = self.execute_sql_with_return(sql, params);
-        match dao{
-            Ok(dao) => {
-                if dao.len() == 1{
-                    Ok(dao[0].clone())
-                }
-                else{
-                    Err(DbError::new("There should be 1 and only 1 record return here"))
-                }
-            },
-            Err(e) => Err(DbError::new("Error in the query"))
+        let dao = try!(self.execute_sql_with_return(sql, params));
+        match dao.len(){
+            1 => Ok(dao[0].clone()),
+            0 => Err(DbError::not_found("There is no record returned")),
+            _ => Err(DbError::new("There should be 1 and only 1 record return here")),
         }
     }
     
@@ -181,8 +472,7 @@ pub trait Database{
     /// build a query, return the sql string and the parameters.
     /// use by select to build the select query
     /// build all types of query
-    /// TODO: need to supply the number of parameters where to start the numbering of the number parameters
-    fn build_query(&self, query:&Query)->SqlFrag{
+    fn build_query(&self, query:&Query)->Result<SqlFrag, DbError>{
         match query.sql_type{
             SqlType::SELECT => self.build_select(query),
             SqlType::INSERT => self.build_insert(query),
@@ -192,15 +482,16 @@ pub trait Database{
     }
     
     /// build operand, i.e: columns, query, function, values
-    fn build_operand(&self, w: &mut SqlFrag, parent_query:&Query, operand:&Operand){
+    fn build_operand(&self, w: &mut SqlFrag, parent_query:&Query, operand:&Operand)->Result<(), DbError>{
         match *operand{
             Operand::ColumnName(ref column_name) => {
-                if parent_query.joins.is_empty(){
+                //columns from outside of this query (correlated subqueries) keeps their table
+                if parent_query.joins.is_empty() && parent_query.is_own_column(column_name){
                     w.append(&column_name.column);
                 }else{
                     w.append(&column_name.complete_name());
                 }
-            }, 
+            },
             Operand::TableName(ref table_name) => {
                 if self.sql_options().contains(&SqlOption::UsesSchema){
                     w.append(&table_name.complete_name());
@@ -209,76 +500,180 @@ pub trait Database{
                 }
             },
             Operand::Function(ref function)=>{
+                    w.append(&function.function);
                     w.append("(");
                     let mut do_comma = false;
                     for param in &function.params{
                         if do_comma{ w.commasp(); }else{ do_comma = true;}
-                        self.build_operand(w, parent_query, param);
+                        try!(self.build_operand(w, parent_query, param));
                     }
                     w.append(")");
                 },
             Operand::Query(ref q) => {
-                panic!("TODO: causes error Attributes 'readnone and readonly' are incompatible! LLVM ERROR: Broken function found, compilation aborted!")
-                //let sql_frag = &self.build_query(&q);
-                //w.append(&sql_frag.sql);
+                try!(self.build_subquery(w, q));
             },
             Operand::Value(ref value) => {
                 w.parameter(value.clone());
             },
+            Operand::JsonPath(ref json_path) => {
+                try!(self.build_json_path(w, parent_query, json_path));
+            },
+            Operand::Sql(ref sql) => {
+                w.append(sql);
+            },
             Operand::Vec(ref operands) => {
                 let mut do_comma = false;
                 if !operands.is_empty(){
                     w.append("(");
                     for op in operands{
-                        println!("op: {:?}",op);
                         if do_comma {w.commasp();}else{do_comma = true;}
-                        self.build_operand(w, parent_query, op);
+                        try!(self.build_operand(w, parent_query, op));
                     }
                     w.append(")");
                 }
             },
         };
+        Ok(())
     }
-    
-    fn build_condition(&self, w: &mut SqlFrag, parent_query:&Query, cond:&Condition){
-        self.build_operand(w, parent_query, &cond.left);
+
+    /// build the extraction of the value at the json path,
+    /// using the json operators on postgresql: info->'dimension'->>'unit'
+    /// and json_extract on sqlite: json_extract(info, '$."dimension"."unit"')
+    fn build_json_path(&self, w: &mut SqlFrag, parent_query:&Query, json_path:&JsonPath)->Result<(), DbError>{
+        fn quote(s: &str)->String{
+            format!("'{}'", s.replace("'", "''"))
+        }
+        let column = Operand::ColumnName(json_path.column.clone());
+        if self.sql_options().contains(&SqlOption::SupportsJsonOperators){
+            try!(self.build_operand(w, parent_query, &column));
+            for (i, key) in json_path.path.iter().enumerate(){
+                let is_last = i == json_path.path.len() - 1;
+                w.append(if is_last && json_path.as_text { "->>" } else { "->" });
+                match *key{
+                    JsonSegment::Index(index) => w.append(&format!("{}", index)),
+                    JsonSegment::Key(ref key) => w.append(&quote(key)),
+                };
+            }
+        }else if self.sql_options().contains(&SqlOption::SupportsJsonExtract){
+            w.append("json_extract(");
+            try!(self.build_operand(w, parent_query, &column));
+            let mut path = "$".to_string();
+            for key in &json_path.path{
+                match *key{
+                    JsonSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+                    JsonSegment::Key(ref key) => path.push_str(&format!(".\"{}\"", key.replace("\"", "\\\""))),
+                };
+            }
+            w.commasp();
+            w.append(&quote(&path));
+            w.append(")");
+        }else{
+            return Err(DbError::new("This database does not support extracting values from json"));
+        }
+        Ok(())
+    }
+
+    /// build a nested query enclosed in parenthesis,
+    /// written on the same sql fragment so the numbering of the parameters continues from the outer query
+    fn build_subquery(&self, w: &mut SqlFrag, query:&Query)->Result<(), DbError>{
+        let mut query = query.clone();
+        query.finalize();
+        w.append("(");
+        try!(self.build_select_into(w, &query));
+        w.append(" )");
+        Ok(())
+    }
+
+    fn build_condition(&self, w: &mut SqlFrag, parent_query:&Query, cond:&Condition)->Result<(), DbError>{
+        //exists has no left operand
+        match cond.equality{
+            Equality::EXISTS => {
+                w.append("EXISTS ");
+                try!(self.build_operand(w, parent_query, &cond.right));
+                return Ok(());
+            },
+            Equality::NOT_EXISTS => {
+                w.append("NOT EXISTS ");
+                try!(self.build_operand(w, parent_query, &cond.right));
+                return Ok(());
+            },
+            Equality::ANY | Equality::OVERLAPS => {
+                if !self.sql_options().contains(&SqlOption::SupportsArrays){
+                    return Err(DbError::new("This database does not support arrays"));
+                }
+            },
+            Equality::CONTAINS => {
+                if !self.sql_options().contains(&SqlOption::SupportsArrays)
+                    && !self.sql_options().contains(&SqlOption::SupportsJsonOperators){
+                    return Err(DbError::new("This database does not support the @> operator"));
+                }
+            },
+            Equality::HAS_KEY => {
+                if !self.sql_options().contains(&SqlOption::SupportsJsonOperators){
+                    return Err(DbError::new("This database does not support the json ? operator"));
+                }
+            },
+            Equality::REGEX | Equality::IREGEX => {
+                if !self.sql_options().contains(&SqlOption::SupportsRegex){
+                    return Err(DbError::new("This database does not support the regular expression operators ~ and ~*"));
+                }
+            },
+            Equality::ILIKE if !self.sql_options().contains(&SqlOption::SupportsILike) => {
+                w.append("lower(");
+                try!(self.build_operand(w, parent_query, &cond.left));
+                w.append(") LIKE lower(");
+                try!(self.build_operand(w, parent_query, &cond.right));
+                w.append(")");
+                return Ok(());
+            },
+            Equality::IS_DISTINCT_FROM if self.sql_options().contains(&SqlOption::UsesNullSafeEqual) => {
+                w.append("NOT (");
+                try!(self.build_operand(w, parent_query, &cond.left));
+                w.append(" <=> ");
+                try!(self.build_operand(w, parent_query, &cond.right));
+                w.append(")");
+                return Ok(());
+            },
+            _ => (),
+        };
+        try!(self.build_operand(w, parent_query, &cond.left));
         w.append(" ");
         match cond.equality{
             Equality::EQ => {
                     w.append("= ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::NEQ => {
                     w.append("!= ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::LT => {
                     w.append("< ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::LTE => {
                     w.append("<= ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::GT => {
                     w.append("> ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::GTE => {
                     w.append(">= ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::IN => {
                     w.append("IN ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::NOT_IN => {
                     w.append("NOT IN ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::LIKE => {
                     w.append("LIKE ");
-                    self.build_operand(w, parent_query, &cond.right);
+                    try!(self.build_operand(w, parent_query, &cond.right));
                 },
             Equality::IS_NOT_NULL => {
                     w.append("IS NOT NULL");
@@ -287,11 +682,75 @@ pub trait Database{
             Equality::IS_NULL => {
                 w.append("IS NULL");
             },
+            Equality::ANY => {
+                    w.append("= ANY(");
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                    w.append(")");
+                },
+            Equality::CONTAINS => {
+                    w.append("@> ");
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                },
+            Equality::OVERLAPS => {
+                    w.append("&& ");
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                },
+            Equality::HAS_KEY => {
+                    w.append("? ");
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                },
+            Equality::BETWEEN => {
+                    match cond.right{
+                        Operand::Vec(ref operands) if operands.len() == 2 => {
+                            w.append("BETWEEN ");
+                            try!(self.build_operand(w, parent_query, &operands[0]));
+                            w.append(" AND ");
+                            try!(self.build_operand(w, parent_query, &operands[1]));
+                        },
+                        _ => return Err(DbError::new("BETWEEN requires the 2 operands, low and high")),
+                    }
+                },
+            Equality::NOT_LIKE => {
+                    w.append("NOT LIKE ");
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                },
+            Equality::ILIKE => {
+                    w.append("ILIKE ");
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                },
+            Equality::REGEX => {
+                    w.append("~ ");
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                },
+            Equality::IREGEX => {
+                    w.append("~* ");
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                },
+            Equality::IS_DISTINCT_FROM | Equality::IS_NOT_DISTINCT_FROM => {
+                    let is_distinct = match cond.equality{
+                        Equality::IS_DISTINCT_FROM => true,
+                        _ => false,
+                    };
+                    let options = self.sql_options();
+                    if options.contains(&SqlOption::SupportsDistinctFrom){
+                        w.append(if is_distinct { "IS DISTINCT FROM " } else { "IS NOT DISTINCT FROM " });
+                    }else if options.contains(&SqlOption::UsesIsForDistinctFrom){
+                        w.append(if is_distinct { "IS NOT " } else { "IS " });
+                    }else if options.contains(&SqlOption::UsesNullSafeEqual){
+                        // IS DISTINCT FROM is written as NOT (left <=> right) above
+                        w.append("<=> ");
+                    }else{
+                        return Err(DbError::new("This database does not support IS DISTINCT FROM"));
+                    }
+                    try!(self.build_operand(w, parent_query, &cond.right));
+                },
+            Equality::EXISTS | Equality::NOT_EXISTS => unreachable!(),
         };
+        Ok(())
     }
     
-    fn build_field(&self, w: &mut SqlFrag, parent_query:&Query, field:&Field){
-        self.build_operand(w, parent_query, &field.operand);
+    fn build_field(&self, w: &mut SqlFrag, parent_query:&Query, field:&Field)->Result<(), DbError>{
+        try!(self.build_operand(w, parent_query, &field.operand));
         match field.name{
             Some(ref name) => {
                 w.append(" AS ");
@@ -299,33 +758,110 @@ pub trait Database{
             }
             None => (),
         };
+        Ok(())
     }
     
     
-    fn build_filter(&self, w: &mut SqlFrag, parent_query:&Query, filter:&Filter){
-        if !filter.subfilters.is_empty(){
+    /// build a filter which is an operand of an expression that binds as tight as `precedence`
+    fn build_sub_filter(&self, w: &mut SqlFrag, parent_query:&Query, filter:&Filter, precedence: u8)->Result<(), DbError>{
+        if filter_precedence(filter) < precedence{
             w.append("( ");
+            try!(self.build_filter(w, parent_query, filter));
+            if !w.sql.ends_with(" "){
+                w.append(" ");
+            }
+            w.append(")");
+        }else{
+            try!(self.build_filter(w, parent_query, filter));
         }
-        self.build_condition(w, parent_query, &filter.condition);
-        for filt in &filter.subfilters{
-            match filt.connector{
-                Connector::And =>{
-                    w.append("AND ");
+        Ok(())
+    }
+
+    fn build_filter(&self, w: &mut SqlFrag, parent_query:&Query, filter:&Filter)->Result<(), DbError>{
+        let (filters, connector, precedence) = match *filter{
+            Filter::Condition(ref condition) => {
+                return self.build_condition(w, parent_query, condition);
+            },
+            Filter::Not(ref filter) => {
+                w.append("NOT ");
+                return self.build_sub_filter(w, parent_query, filter, 3);
+            },
+            Filter::All(ref filters) => {
+                if filters.is_empty(){
+                    w.append("1 = 1 ");
+                    return Ok(());
                 }
-                Connector::Or => {
-                    w.append("OR ");
+                (filters, "AND ", 2)
+            },
+            Filter::Any(ref filters) => {
+                if filters.is_empty(){
+                    w.append("1 = 0 ");
+                    return Ok(());
                 }
+                (filters, "OR ", 1)
+            },
+        };
+        let mut do_connect = false;
+        for filt in filters{
+            if do_connect{
+                if !w.sql.ends_with(" "){
+                    w.append(" ");
+                }
+                w.append(connector);
+            }else{
+                do_connect = true;
             }
-            self.build_filter(w, parent_query, filt);// build sub filters as well
+            try!(self.build_sub_filter(w, parent_query, filt, precedence));
         }
-        if !filter.subfilters.is_empty(){
-            w.append(" )");
+        Ok(())
+    }
+
+    /// build an item of the ORDER BY clause,
+    /// NULLS FIRST/LAST is emulated by ordering on a CASE of the nulls before the item itself
+    fn build_order(&self, w: &mut SqlFrag, parent_query:&Query, order:&Order)->Result<(), DbError>{
+        if let Operand::ColumnName(ref column_name) = order.operand{
+            let name = column_name.super_complete_name();
+            if !ColumnName::is_column_name(&name){
+                return Err(DbError::new(&format!("Unable to order by {}, it is not a column name, use order_by_sql for sql expressions", name)));
+            }
         }
+        let supports_nulls = self.sql_options().contains(&SqlOption::SupportsNullsOrder);
+        if !supports_nulls{
+            if let Some(ref nulls_where) = order.nulls_where{
+                w.append("CASE WHEN ");
+                try!(self.build_operand(w, parent_query, &order.operand));
+                if !w.sql.ends_with(" "){
+                    w.append(" ");
+                }
+                match *nulls_where{
+                    NullsWhere::FIRST => w.append("IS NULL THEN 0 ELSE 1 END"),
+                    NullsWhere::LAST => w.append("IS NULL THEN 1 ELSE 0 END"),
+                };
+                w.commasp();
+            }
+        }
+        try!(self.build_operand(w, parent_query, &order.operand));
+        if !w.sql.ends_with(" "){
+            w.append(" ");
+        }
+        match order.direction{
+            Direction::ASC => w.append("ASC"),
+            Direction::DESC => w.append("DESC"),
+        };
+        if supports_nulls{
+            match order.nulls_where{
+                Some(NullsWhere::FIRST) => {w.append(" NULLS FIRST");},
+                Some(NullsWhere::LAST) => {w.append(" NULLS LAST");},
+                None => (),
+            }
+        }
+        Ok(())
     }
-    
+
     /// build the filter clause or the where clause of the query
-    /// TODO: add the sub filters
-    fn build_filters(&self, w: &mut SqlFrag, parent_query:&Query, filters: &Vec<Filter>){
+    fn build_filters(&self, w: &mut SqlFrag, parent_query:&Query, filters: &Vec<Filter>)->Result<(), DbError>{
+        // a lone filter needs no grouping, several are joined with AND
+        let precedence = if filters.len() > 1 { 2 } else { 0 };
         let mut do_and = false;
         for filter in filters{
             if do_and{
@@ -333,12 +869,13 @@ pub trait Database{
             }else{
                 do_and = true;
             }
-            self.build_filter(w, parent_query, filter);
+            try!(self.build_sub_filter(w, parent_query, filter, precedence));
         }
+        Ok(())
     }
 
     /// build the enumerated, distinct, *, columns
-    fn build_enumerated_fields(&self, w: &mut SqlFrag, parent_query:&Query, enumerated_fields: &Vec<Field>){
+    fn build_enumerated_fields(&self, w: &mut SqlFrag, parent_query:&Query, enumerated_fields: &Vec<Field>)->Result<(), DbError>{
         let mut do_comma = false;
         let mut cnt = 0;
         for field in enumerated_fields{
@@ -347,24 +884,82 @@ pub trait Database{
             if cnt % 4 == 0{//break at every 4 columns to encourage sql tuning/revising
                 w.left_river("");
             }
-            self.build_field(w, parent_query, field);
+            try!(self.build_field(w, parent_query, field));
         }
+        Ok(())
     }
 
     /// build the select statment from the query object
-    fn build_select(&self, query: &Query)->SqlFrag{
+    fn build_select(&self, query: &Query)->Result<SqlFrag, DbError>{
         let mut w = SqlFrag::new(self.sql_options());
+        try!(self.build_select_into(&mut w, query));
+        Ok(w)
+    }
+
+    /// build the WITH clause of the queries declared in this query
+    fn build_declared_queries(&self, w: &mut SqlFrag, query: &Query)->Result<(), DbError>{
+        if !self.sql_options().contains(&SqlOption::SupportsCTE){
+            return Err(DbError::new("This database does not support common table expressions (WITH queries)"));
+        }
+        w.left_river("WITH");
+        if query.has_recursive_declared_query(){
+            w.append("RECURSIVE ");
+        }
+        let mut do_comma = false;
+        for declared in &query.declared_query{
+            if do_comma{ w.comma(); }else{ do_comma = true; }
+            w.append(&declared.alias);
+            w.append(" AS (");
+            let mut declared_query = declared.query.clone();
+            declared_query.finalize();
+            try!(self.build_select_into(w, &declared_query));
+            match declared.recursive_query{
+                Some(ref recursive_query) => {
+                    let mut recursive_query = recursive_query.clone();
+                    recursive_query.finalize();
+                    if declared.union_all{
+                        w.left_river("UNION ALL");
+                    }else{
+                        w.left_river("UNION");
+                    }
+                    try!(self.build_select_into(w, &recursive_query));
+                },
+                None => (),
+            };
+            w.append(" )");
+        }
+        Ok(())
+    }
+
+    /// build the select statement into an existing sql fragment,
+    /// used directly when the select is nested in another query
+    fn build_select_into(&self, w: &mut SqlFrag, query: &Query)->Result<(), DbError>{
+        if !query.declared_query.is_empty(){
+            try!(self.build_declared_queries(w, query));
+        }
         w.left_river("SELECT");
-        self.build_enumerated_fields(&mut w, query, &query.enumerated_fields); //TODO: add support for column_sql, fields, functions
+        if !query.distinct_on_columns.is_empty(){
+            if !self.sql_options().contains(&SqlOption::SupportsDistinctOn){
+                return Err(DbError::new("This database does not support DISTINCT ON, use DISTINCT instead"));
+            }
+            w.append("DISTINCT ON (");
+            let mut do_comma = false;
+            for column in &query.distinct_on_columns{
+                if do_comma{ w.commasp(); }else{ do_comma = true; }
+                let operand = Operand::ColumnName(ColumnName::from_str(column));
+                try!(self.build_operand(w, query, &operand));
+            }
+            w.append(") ");
+        }
+        else if query.distinct{
+            w.append("DISTINCT ");
+        }
+        try!(self.build_enumerated_fields(w, query, &query.enumerated_fields)); //TODO: add support for column_sql, fields, functions
         w.left_river("FROM");
-        
-        assert!(query.from.is_some(), "There should be table, query, function to select from");
-        
+
         match query.from{
-            Some(ref field) => {
-                self.build_field(&mut w, query, field);
-            }
-            None => println!("Warning: No from in this query"),
+            Some(ref field) => try!(self.build_field(w, query, field)),
+            None => return Err(DbError::new("There should be table, query, function to select from")),
         };
         if !query.joins.is_empty(){
             for join in &query.joins{
@@ -376,7 +971,11 @@ pub trait Database{
                                 Modifier::FULL => w.right_river("FULL "),
                             };
                         },
-                    None => ()
+                    None => {
+                        // INNER, CROSS and plain joins start on their own line as well,
+                        // otherwise the join is appended right after the table name
+                        w.right_river("");
+                    }
                 };
                 match join.join_type{
                     Some(ref join_type) => {
@@ -391,7 +990,9 @@ pub trait Database{
                 w.append("JOIN ");
                 w.append(&join.table_name.complete_name());
                 w.append(" ");
-                assert!(join.column1.len() == join.column2.len(), "There should be equal number of corresponding columns to join");
+                if join.column1.len() != join.column2.len(){
+                    return Err(DbError::new("There should be equal number of corresponding columns to join"));
+                }
                 let mut cnt = 0;
                 let mut do_and = false;
                 for jc in &join.column1{
@@ -412,7 +1013,7 @@ pub trait Database{
         
         if !query.filters.is_empty() {
             w.left_river("WHERE ");
-            self.build_filters(&mut w, query, &query.filters);
+            try!(self.build_filters(w, query, &query.filters));
         }
         
         if !query.group_by.is_empty() {
@@ -420,30 +1021,22 @@ pub trait Database{
             let mut do_comma = false;
             for operand in &query.group_by{
                 if do_comma{ w.comma(); }else{ do_comma = true;}
-                self.build_operand(&mut w, query, operand);
+                try!(self.build_operand(w, query, operand));
                 w.append(" ");
             }
         };
         
         if !query.having.is_empty() {
             w.left_river("HAVING ");
-            let mut do_comma = false;
-            for hav in &query.having{
-                if do_comma { w.commasp(); }else{ do_comma=true; }
-                self.build_condition(&mut w, query, hav);
-            }
+            try!(self.build_filters(w, query, &query.having));
         }
         
         if !query.order_by.is_empty(){
             w.left_river("ORDER BY ");
             let mut do_comma = false;
-            for &(ref column, ref direction) in &query.order_by{
+            for order in &query.order_by{
                 if do_comma { w.commasp();} else { do_comma = true;}
-                w.append(&column);
-                match *direction{
-                    Direction::ASC => w.append(" ASC"),
-                    Direction::DESC => w.append(" DESC")
-                };
+                try!(self.build_order(w, query, order));
             }
         };
         
@@ -458,94 +1051,152 @@ pub trait Database{
         match query.page{
             Some(page) =>{
                 w.left_river("OFFSET ");
-                assert!(query.page_size.is_some(), "Page size should be specified when paging");
-                let page_size = query.page_size.unwrap();
+                let page_size = match query.page_size{
+                    Some(page_size) => page_size,
+                    None => return Err(DbError::new("Page size should be specified when paging")),
+                };
                 let offset = page * page_size;
                 w.append(&format!("{}",offset));
             },
             None => (),
         };
-        w
+        Ok(())
     }
     
     /// TODO complete this
-    fn build_insert(&self, query: &Query)->SqlFrag{
-        println!("building insert query");
+    fn build_insert(&self, query: &Query)->Result<SqlFrag, DbError>{
         let mut w = SqlFrag::new(self.sql_options());
         w.left_river("INSERT");
         w.append("INTO ");
-        let into_table = query.get_from_table();
-        assert!(into_table.is_some(), "There should be table to insert to");
-        if into_table.is_some(){
-            let table_name = into_table.unwrap();
-            if self.sql_options().contains(&SqlOption::UsesSchema){
-                w.append(&table_name.complete_name());
-            }else{
-                w.append(&table_name.name);
-            }
+        let table_name = match query.get_from_table(){
+            Some(table_name) => table_name,
+            None => return Err(DbError::new("There should be table to insert to")),
+        };
+        if self.sql_options().contains(&SqlOption::UsesSchema){
+            w.append(&table_name.complete_name());
+        }else{
+            w.append(&table_name.name);
         }
         
         
         w.append("( ");
-        self.build_enumerated_fields(&mut w, query, &query.enumerated_fields); //TODO: add support for column_sql, fields, functions
+        try!(self.build_enumerated_fields(&mut w, query, &query.enumerated_fields)); //TODO: add support for column_sql, fields, functions
         w.append(" ) ");
-        assert!(!query.values.is_empty(), "values should not be empty, when inserting records");
-        if !query.values.is_empty(){
-            w.left_river("VALUES");
+        if query.values.is_empty(){
+            return Err(DbError::new("values should not be empty, when inserting records"));
+        }
+        //values are laid out row after row, each row has a value for each of the columns
+        let column_count = query.get_enumerated_columns().len();
+        if column_count == 0 || query.values.len() % column_count != 0{
+            return Err(DbError::new(&format!("{} values does not fit into rows of {} columns", query.values.len(), column_count)));
+        }
+        w.left_river("VALUES");
+        let mut do_row_comma = false;
+        for row in query.values.chunks(column_count){
+            if do_row_comma{ w.comma(); w.right_river(""); }else{ do_row_comma = true; }
             w.append("(");
             let mut do_comma = false;
-            for vo in &query.values{
+            for vo in row{
                 if do_comma{ w.commasp(); } else{do_comma=true;}
-                self.build_operand(&mut w, query, vo);
+                try!(self.build_operand(&mut w, query, vo));
             }
             w.append(") ");
         }
+        if query.is_upsert(){
+            try!(self.build_upsert(&mut w, query));
+        }
         if !query.enumerated_returns.is_empty() {
             if self.sql_options().contains(&SqlOption::SupportsReturningClause) {
                 w.left_river("RETURNING");
                 let mut do_comma = false;
                 for field in &query.enumerated_returns{
                     if do_comma{ w.commasp(); }else {do_comma = true;}
-                    self.build_field(&mut w, query, field);
+                    try!(self.build_field(&mut w, query, field));
                 }
             }
         }
         w.ln();
-        w
+        Ok(w)
+    }
+
+    /// the conflict handling part of the insert, which turns it into an upsert
+    fn build_upsert(&self, w: &mut SqlFrag, query: &Query)->Result<(), DbError>{
+        let update_columns = query.get_conflict_update_columns();
+        if self.sql_options().contains(&SqlOption::SupportsOnConflict){
+            w.left_river("ON");
+            w.append("CONFLICT (");
+            let mut do_comma = false;
+            for c in &query.conflict_columns{
+                if do_comma{ w.commasp(); }else{ do_comma = true; }
+                w.append(&c.column);
+            }
+            w.append(") ");
+            if update_columns.is_empty(){
+                w.append("DO NOTHING ");
+            }else{
+                w.append("DO UPDATE");
+                w.left_river("SET");
+                let mut do_comma = false;
+                for c in &update_columns{
+                    if do_comma{ w.commasp(); }else{ do_comma = true; }
+                    w.append(&format!("{} = EXCLUDED.{}", c.column, c.column));
+                }
+            }
+            Ok(())
+        }
+        else if self.sql_options().contains(&SqlOption::SupportsOnDuplicateKey){
+            w.left_river("ON");
+            w.append("DUPLICATE KEY UPDATE");
+            w.right_river("");
+            if update_columns.is_empty(){
+                // nothing to update, assign a conflict column to itself to leave the record as is
+                let c = &query.conflict_columns[0];
+                w.append(&format!("{} = {}", c.column, c.column));
+            }else{
+                let mut do_comma = false;
+                for c in &update_columns{
+                    if do_comma{ w.commasp(); }else{ do_comma = true; }
+                    w.append(&format!("{} = VALUES({})", c.column, c.column));
+                }
+            }
+            Ok(())
+        }
+        else{
+            Err(DbError::new("This database does not support upsert"))
+        }
     }
 
     
-    fn build_update(&self, query: &Query)->SqlFrag{
+    fn build_update(&self, query: &Query)->Result<SqlFrag, DbError>{
         let mut w = SqlFrag::new(self.sql_options());
         w.left_river("UPDATE ");
-        let from_table = query.get_from_table();
-        assert!(from_table.is_some(), "There should be table to update from");
-        if from_table.is_some(){
-            w.append(&from_table.unwrap().complete_name());
+        let table_name = match query.get_from_table(){
+            Some(table_name) => table_name,
+            None => return Err(DbError::new("There should be table to update from")),
+        };
+        if self.sql_options().contains(&SqlOption::UsesSchema){
+            w.append(&table_name.complete_name());
+        }else{
+            w.append(&table_name.name);
         }
         let enumerated_columns = query.get_enumerated_columns();
+        if enumerated_columns.len() != query.values.len(){
+            return Err(DbError::new(&format!("There are {} columns to update but {} values", enumerated_columns.len(), query.values.len())));
+        }
         let mut do_comma = false;
         if !enumerated_columns.is_empty(){
             w.left_river("SET ");
         }
-        let mut column_index = 0;
-        for ec in &enumerated_columns{
+        for (ec, value) in enumerated_columns.iter().zip(query.values.iter()){
             if do_comma{ w.commasp(); } else{do_comma = true;}
             w.append(&ec.column);
             w.append(" = ");
-            let value = &query.values[column_index];
-            match value{
-                &Operand::Value(ref value) => {
-                    w.parameter(value.clone());
-                },
-                _ => {}
-            }
-            column_index += 1;
+            try!(self.build_operand(&mut w, query, value));
         }
        
         if !query.filters.is_empty() {
             w.left_river("WHERE ");
-            self.build_filters(&mut w, query, &query.filters);
+            try!(self.build_filters(&mut w, query, &query.filters));
         }
         if !query.enumerated_returns.is_empty() {
             if self.sql_options().contains(&SqlOption::SupportsReturningClause) {
@@ -553,30 +1204,38 @@ pub trait Database{
                 let mut do_comma = false;
                 for field in &query.enumerated_returns{
                     if do_comma{ w.commasp(); }else {do_comma = true;}
-                    self.build_field(&mut w, query, field);
+                    try!(self.build_field(&mut w, query, field));
                 }
             }
         }
-        w
+        Ok(w)
     }
 
-    fn build_delete(&self, query: &Query)->SqlFrag{
+    fn build_delete(&self, query: &Query)->Result<SqlFrag, DbError>{
         let mut w = SqlFrag::new(self.sql_options());
         w.left_river("DELETE FROM ");
-        let from_table = query.get_from_table();
-        assert!(from_table.is_some(), "There should be table to delete from");
-        if from_table.is_some(){
-            w.append(&from_table.unwrap().complete_name());
+        let table_name = match query.get_from_table(){
+            Some(table_name) => table_name,
+            None => return Err(DbError::new("There should be table to delete from")),
+        };
+        if self.sql_options().contains(&SqlOption::UsesSchema){
+            w.append(&table_name.complete_name());
+        }else{
+            w.append(&table_name.name);
         }
         if !query.filters.is_empty() {
             w.left_river("WHERE ");
-            self.build_filters(&mut w, query, &query.filters);
+            try!(self.build_filters(&mut w, query, &query.filters));
         }
-        w
+        Ok(w)
     }
 
     fn sql_options(&self)->Vec<SqlOption>;
 
+    /// the maximum number of parameters a statement can have,
+    /// bulk inserts are split into several statements to stay within this limit
+    fn max_parameters(&self)->usize;
+
 }
 
 
@@ -589,28 +1248,28 @@ pub trait DatabaseDDL{
     ////////////////////////////////////////
 
     /// create a database schema
-    fn create_schema(&self, schema:&str);
+    fn create_schema(&self, schema:&str)->Result<(), DbError>;
 
     /// drop the database schema
-    fn drop_schema(&self, schema:&str);
+    fn drop_schema(&self, schema:&str)->Result<(), DbError>;
 
     /// create a database table based on the Model Definition
-    fn create_table(&self, model:&Table);
+    fn create_table(&self, model:&Table)->Result<(), DbError>;
     
     /// build sql for create table
-    fn build_create_table(&self, table:&Table)->SqlFrag;
+    fn build_create_table(&self, table:&Table)->Result<SqlFrag, DbError>;
 
     /// rename table, in the same schema
-    fn rename_table(&self, table:&Table, new_tablename:String);
+    fn rename_table(&self, table:&Table, new_tablename:String)->Result<(), DbError>;
 
     /// drop table
-    fn drop_table(&self, table:&Table);
+    fn drop_table(&self, table:&Table)->Result<(), DbError>;
 
     /// set the foreign key constraint of a table
-    fn set_foreign_constraint(&self, model:&Table);
+    fn set_foreign_constraint(&self, model:&Table)->Result<(), DbError>;
 
     /// set the primary key constraint of a table
-    fn set_primary_constraint(&self, model:&Table);
+    fn set_primary_constraint(&self, model:&Table)->Result<(), DbError>;
 }
 
 
@@ -623,26 +1282,26 @@ pub trait DatabaseDev{
 ////////////////////////////////////////////
 
     /// applicable to later version of postgresql where there is inheritance
-    fn get_table_sub_class(&self, schema:&str, table:&str)->Vec<String>;
+    fn get_table_sub_class(&self, schema:&str, table:&str)->Result<Vec<String>, DbError>;
 
-    fn get_parent_table(&self, schema:&str, table:&str)->Option<String>;
+    fn get_parent_table(&self, schema:&str, table:&str)->Result<Option<String>, DbError>;
 
     ////
     /// Build the Table object based on the extracted meta data info from database
     /// This is queries directly from the database, so this will be costly. Only used this on initialization processes
     ///
-    fn get_table_metadata(&self, schema:&str, table:&str, is_view: bool)->Table;
+    fn get_table_metadata(&self, schema:&str, table:&str, is_view: bool)->Result<Table, DbError>;
 
     /// get all the tables in this database (schema, table, is_view)
-    fn get_all_tables(&self)->Vec<(String, String, bool)>;
+    fn get_all_tables(&self)->Result<Vec<(String, String, bool)>, DbError>;
 
     /// get the inherited columns of this table
-    fn get_inherited_columns(&self, schema:&str, table:&str)->Vec<String>;
+    fn get_inherited_columns(&self, schema:&str, table:&str)->Result<Vec<String>, DbError>;
 
     ///get the equivalent postgresql database data type to rust data type
     /// returns (module, type)
-    fn dbtype_to_rust_type(&self, db_type: &str)->(Vec<String>, String);
+    fn dbtype_to_rust_type(&self, db_type: &str)->Result<(Vec<String>, String), DbError>;
     
-    fn rust_type_to_dbtype(&self, rust_type: &str)->String;
+    fn rust_type_to_dbtype(&self, rust_type: &str)->Result<String, DbError>;
 
 }
@@ -1,8 +1,8 @@
 use query::{Filter,Operand};
 use query::Query;
-use table::Table;
-use dao::{Dao};
-use database::{Database, DbError};
+use table::{Table, Column};
+use dao::{Dao, DaoResult, Value};
+use database::{Database, DbError, SqlOption, Transaction};
 use table::IsTable;
 use dao::IsDao;
 use dao::ToValue;
@@ -22,16 +22,13 @@ impl <'a>EntityManager<'a>{
     }
 
     /// delete records of this table
-    pub fn delete(&self, table:&Table, filters:Vec<Filter>)->usize{
+    pub fn delete(&self, table:&Table, filters:Vec<Filter>)->Result<usize, DbError>{
         let mut query = Query::delete();
         query.from(table);
         for filter in filters{
             query.add_filter(filter);
         }
-        match query.execute(self.db){
-            Ok(x) => x,
-            Err(e) => panic!("Error deleting record {}",e),
-        }
+        query.execute(self.db)
     }
 
     /// get all the records of this table
@@ -105,7 +102,9 @@ impl <'a>EntityManager<'a>{
         where T : IsTable + IsDao{
         let table = T::table();
         let primary = table.primary_columns();
-        assert!(primary.len() == 1, "There should only be 1 primary column for this to work");
+        if primary.len() != 1{
+            return Err(DbError::new(&format!("There should only be 1 primary column in table {} for this to work", table.complete_name())));
+        }
         let pk = primary[0].name.to_string();
         
         Query::select_all()
@@ -135,6 +134,47 @@ impl <'a>EntityManager<'a>{
         q.collect_one(self.db)
     }
 
+    /// insert the records in bulk, using as few statements as the parameter limit of the database allows
+    /// all the records should have the same set of columns
+    /// returns the inserted records when the database supports the returning clause, otherwise empty
+    pub fn insert_many<T>(&self, daos:Vec<Dao>)->Result<Vec<T>, DbError>
+        where T : IsTable + IsDao{
+        if daos.is_empty(){
+            return Ok(vec![]);
+        }
+        let table = T::table();
+        let mut q = Query::insert();
+        q.into_table(&table.complete_name());
+        let columns:Vec<String> = daos[0].values.keys().cloned().collect();
+        for c in &columns{
+            q.column(c);
+        }
+        for dao in &daos{
+            if dao.values.len() != columns.len(){
+                return Err(DbError::new("All the records should have the same columns when inserting in bulk"));
+            }
+            for c in &columns{
+                match dao.values.get(c){
+                    Some(value) => {
+                        q.add_value(Operand::Value(value.clone()));
+                    },
+                    None => return Err(DbError::new(&format!("Column {} is missing in one of the records", c))),
+                };
+            }
+        }
+        q.return_all();
+        q.finalize();
+        let inserted = try!(self.db.insert_many(&q));
+        let dao_result = DaoResult{
+            dao: inserted,
+            renamed_columns: vec![],
+            total: None,
+            page: None,
+            page_size: None,
+        };
+        dao_result.cast()
+    }
+
     /// insert this record on the database, ignoring some columns
     /// which are set by the database default
     /// columns that are ignored are set by the database automatically
@@ -160,72 +200,233 @@ impl <'a>EntityManager<'a>{
         q.collect_one(self.db)
     }
 
-    /// insert this record on the database, explicitly setting the defaults of the columns
+    /// insert this record on the database, leaving out the columns that has a default,
+    /// so the database sets them, ie: `created` now()
     /// it may produce the same result with insert_with_ignore_columns
-    /// the query is different since it may mentions `created` now(),
     pub fn insert_ignore_defaulted_columns<T>(&self, dao:Dao)->Result<T, DbError>
         where T: IsTable + IsDao {
-        panic!("not yet")
+        let table = T::table();
+        let defaulted = table.columns.iter()
+            .filter(|c| c.default.is_some())
+            .map(|c| c.name.as_str())
+            .collect();
+        self.insert_with_ignore_columns(dao, defaulted)
     }
 
     /// this is called when there is a problem with the transaction
-    pub fn reset(&self){
-        panic!("not yet")
+    pub fn reset(&self)->Result<(), DbError>{
+        self.db.reset()
     }
 
     /// when there is a problem with the transaction process, this can be called
-    pub fn rollback(&self){
-        panic!("not yet")
+    pub fn rollback(&self)->Result<(), DbError>{
+        self.db.rollback()
+    }
+
+    /// run the closure in a transaction,
+    /// committed when the closure returns Ok, rolled back when it returns Err,
+    /// the error of the closure is returned even when the rollback also fails
+    pub fn transaction<F, R>(&self, f: F)->Result<R, DbError>
+        where F: FnOnce(&EntityManager)->Result<R, DbError>{
+        let tx = try!(Transaction::new(self.db));
+        match f(self){
+            Ok(result) => {
+                try!(tx.commit());
+                Ok(result)
+            },
+            Err(e) => {
+                let _ = tx.rollback();
+                Err(e)
+            }
+        }
     }
 
     /// update the Dao, return the updated Dao
     pub fn update<T>(&self, dao:&Dao)->Result<T, DbError>
         where T: IsTable + IsDao {
-        panic!("not yet")
+        self.update_ignore_columns(dao, vec![])
     }
 
     /// update the Dao, return the updated Dao
     /// ignored columns will remain unchanged
     pub fn update_ignore_columns<T>(&self, dao:&Dao, ignore_columns:Vec<&str>)->Result<T, DbError>
         where T: IsTable + IsDao {
-        panic!("not yet")
+        let table = T::table();
+        let filters = try!(self.primary_filters(&table, dao));
+        let mut q = self.update_query(&table, dao);
+        q.exclude_columns(ignore_columns);
+        self.execute_update(q, filters)
     }
 
     /// update the Dao, return the updated Dao
     /// only the columns specified, the rest is unchanged
     pub fn update_only_columns<T>(&self, dao:&Dao, columns:Vec<&str>)->Result<T, DbError>
         where T: IsTable + IsDao {
-        panic!("not yet")
+        let table = T::table();
+        let filters = try!(self.primary_filters(&table, dao));
+        let mut q = self.update_query(&table, dao);
+        for key in dao.values.keys(){
+            if !columns.contains(&key.as_str()){
+                q.exclude_column(key);
+            }
+        }
+        self.execute_update(q, filters)
     }
 
     /// update the Dao, return the updated Dao
-    /// the default columns will be reset to whatever the db's default function will come up.
-    /// ie. updated column will be defaulted everytime a record is updated.
+    /// the columns that has a default are left unchanged by the update,
+    /// ie. `created` keeps the value the database set when the record was inserted.
     pub fn update_ignore_defaulted_columns<T>(&self, dao:&Dao)->Result<T, DbError>
         where T: IsTable + IsDao {
-        panic!("not yet")
+        let table = T::table();
+        let defaulted = table.columns.iter()
+            .filter(|c| c.default.is_some())
+            .map(|c| c.name.as_str())
+            .collect();
+        self.update_ignore_columns(dao, defaulted)
     }
 
     /// update the Dao with filter, return the updated Dao
+    /// the filter should match only 1 record, it is checked before updating
+    /// so nothing is updated when it matches none or several records
     pub fn update_with_filter<T>(&self, dao:&Dao, filter:Vec<Filter>)->Result<T, DbError>
         where T: IsTable + IsDao {
-        panic!("not yet")
+        let table = T::table();
+        let keys = try!(self.matching_primary_filters(&table, &filter));
+        let q = self.update_query(&table, dao);
+        self.execute_update(q, keys)
     }
-    
+
+    /// the update query setting all the columns of the dao, except for the primary columns
+    fn update_query(&self, table:&Table, dao:&Dao)->Query{
+        let mut q = Query::update();
+        q.from_table(&table.complete_name());
+        for (column, value) in &dao.values{
+            q.column(column);
+            q.add_value(Operand::Value(value.clone()));
+        }
+        for pk in table.primary_columns(){
+            q.exclude_column(&pk.name);
+        }
+        q
+    }
+
+    /// filters matching the primary columns of the table to the values in the dao
+    fn primary_filters(&self, table:&Table, dao:&Dao)->Result<Vec<Filter>, DbError>{
+        let primary = table.primary_columns();
+        if primary.is_empty(){
+            return Err(DbError::new(&format!("Table {} has no primary columns to identify the record", table.complete_name())));
+        }
+        let mut filters = vec![];
+        for pk in primary{
+            match dao.values.get(&pk.name){
+                Some(&Value::Null) | None => return Err(DbError::new(&format!("The value of primary column {} is not set", pk.name))),
+                Some(value) => filters.push(Filter::new(&pk.name, Equality::EQ, value)),
+            };
+        }
+        Ok(filters)
+    }
+
+    /// the primary key filters of the only record matching the filters
+    fn matching_primary_filters(&self, table:&Table, filters:&Vec<Filter>)->Result<Vec<Filter>, DbError>{
+        let mut q = Query::select();
+        for pk in table.primary_columns(){
+            q.column(&pk.name);
+        }
+        q.from_table(&table.complete_name());
+        q.add_filters(filters.clone());
+        let dao = try!(self.db.execute_with_one_return(&q));
+        self.primary_filters(table, &dao)
+    }
+
+    /// update the record identified by the primary column filters in `keys`,
+    /// the primary columns are not updated so the record is retrieved again
+    /// with the `keys` when the database has no returning clause
+    fn execute_update<T>(&self, mut q:Query, keys:Vec<Filter>)->Result<T, DbError>
+        where T: IsTable + IsDao {
+        let with_return = self.db.sql_options().contains(&SqlOption::SupportsReturningClause);
+        q.add_filters(keys.clone());
+        if with_return{
+            q.return_all();
+        }
+        q.finalize();
+        if q.get_enumerated_columns().is_empty(){
+            return Err(DbError::new("There are no columns to update"));
+        }
+        if with_return{
+            let dao = try!(self.db.update(&q));
+            return T::from_dao(&dao);
+        }
+        // mysql counts only the changed records, the record is looked up instead
+        try!(self.db.execute(&q));
+        let mut select = Query::select_all();
+        select.from_table(&T::table().complete_name());
+        select.add_filters(keys);
+        select.collect_one(self.db)
+    }
+
     /// whether to use insert or update
-    /// insert when it is a new record
-    /// update when it is an existing recor
-    /// may use UPSERT in newer versions of postgres
-    /// may use MERGE in oracle, mssql
+    /// insert when it is a new record, the primary key is not set and will be assigned by the database
+    /// otherwise upsert, the existing record with the same primary key is updated.
+    /// When the table has no primary key, the first unique column which has a value identifies the existing record
     pub fn save<T>(&self, dao:T)->Result<T, DbError> where T : IsTable + IsDao{
-        panic!("not yet");
+        let table = T::table();
+        let dao = dao.to_dao();
+        let primary_columns = table.primary_columns();
+        let unique_columns = table.unique_columns();
+        if primary_columns.is_empty() && unique_columns.is_empty(){
+            return Err(DbError::new(&format!("Table {} has no primary or unique columns to identify an existing record", table.complete_name())));
+        }
+        let key_columns = if !primary_columns.is_empty(){
+            primary_columns
+        }else{
+            // only one key can be the conflict target, the unique columns are separate constraints
+            match unique_columns.into_iter().find(|c| has_value(&dao, c)){
+                Some(c) => vec![c],
+                None => vec![],
+            }
+        };
+        if key_columns.is_empty() || !key_columns.iter().all(|c| has_value(&dao, c)){
+            return self.insert(dao);
+        }
+        let mut key_values = vec![];
+        for c in &key_columns{
+            key_values.push((c.name.to_string(), dao.values[&c.name].clone()));
+        }
+        let mut q = Query::insert();
+        q.into_table(&table.complete_name());
+        for (column, value) in &dao.values{
+            q.column(column);
+            q.add_value(Operand::Value(value.clone()));
+        }
+        q.on_conflict(key_values.iter().map(|&(ref column, _)| &column[..]).collect());
+        if self.db.sql_options().contains(&SqlOption::SupportsReturningClause){
+            q.return_all();
+            return q.collect_one(self.db);
+        }
+        try!(q.execute(self.db));
+        // no returning clause, retrieve the saved record
+        let mut select = Query::select_all();
+        select.from_table(&table.complete_name());
+        for &(ref column, ref value) in &key_values{
+            select.filter(column, Equality::EQ, value);
+        }
+        select.collect_one(self.db)
     }
      ///
      /// Search a set of record from the base Query that would have been returned by the base query
      ///
     fn search<T>(&self, keyword:&str)->Result<Vec<T>, DbError>
         where T: IsTable + IsDao {
-        panic!("not yet");
+        Err(DbError::new("Searching is not yet supported"))
     }
 
 }
+
+/// whether the column has a non-null value in the dao
+fn has_value(dao: &Dao, column: &Column)->bool{
+    match dao.values.get(&column.name){
+        Some(&Value::Null) | None => false,
+        Some(_) => true,
+    }
+}
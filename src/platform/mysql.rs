@@ -1,61 +1,213 @@
-use query::Query;
 use dao::Dao;
 
 use dao::Value;
+use dao::FromValue;
 use database::{Database};
 use writer::SqlFrag;
 use database::SqlOption;
 
 use mysql::value::Value as MyValue;
-use mysql::error::MyResult;
+use mysql::error::MyError;
 use mysql::conn::Stmt;
-use mysql::conn::pool::{MyPool};
+use mysql::conn::Column as MyColumn;
+use mysql::consts::ColumnType;
+use mysql::consts::UNSIGNED_FLAG;
+use mysql::conn::pool::{MyPool, MyPooledConn};
 
 use table::Table;
 use database::DatabaseDDL;
-use database::DbError;
+use database::{DbError, DbErrorKind};
+use database::TransactionDepth;
+use std::cell::RefCell;
+use chrono::{Datelike, Timelike};
+use chrono::naive::date::NaiveDate;
+use chrono::naive::time::NaiveTime;
+use rustc_serialize::json::ToJson;
+
+/// the column type of JSON columns (mysql 5.7), which has no variant in `ColumnType`
+/// http://dev.mysql.com/doc/internals/en/com-query-response.html#column-type
+const MYSQL_TYPE_JSON: u8 = 0xf5;
 
 pub struct Mysql {
     pool: Option<MyPool>,
+    /// the pool may hand out a different connection on each statement,
+    /// so a connection is held here for the statements inside a transaction
+    transaction_conn: RefCell<Option<MyPooledConn>>,
+    /// the number of nested transactions in progress on the held connection
+    transaction_depth: TransactionDepth,
+}
+
+/// map the error from the driver to the kind of database error through the mysql error code,
+/// the original error is kept as the cause
+impl From<MyError> for DbError{
+
+    fn from(e: MyError)->Self{
+        let kind = match e{
+            MyError::MySqlError(ref my_error) => Mysql::error_kind(my_error.code, &my_error.state, &my_error.message),
+            MyError::MyIoError(_) => DbErrorKind::Connection,
+            _ => DbErrorKind::Other,
+        };
+        DbError::from_driver(kind, e)
+    }
 }
 
 impl Mysql{
     
     pub fn new()->Self{
-        Mysql{ pool: None }
+        Mysql{ pool: None, transaction_conn: RefCell::new(None), transaction_depth: TransactionDepth::new() }
     }
     
+    /// http://dev.mysql.com/doc/refman/5.7/en/error-messages-server.html
+    fn error_kind(code: u16, state: &str, message: &str)->DbErrorKind{
+        // ie: Duplicate entry 'GTX660' for key 'name'
+        let key = message.split("for key '").nth(1)
+            .and_then(|k| k.split('\'').next())
+            .map(|k| k.to_string());
+        match code{
+            1062 /* ER_DUP_ENTRY */ | 1586 /* ER_DUP_ENTRY_WITH_KEY_NAME */ => DbErrorKind::UniqueViolation{constraint: key},
+            1216 | 1217 | 1451 | 1452 /* ER_ROW_IS_REFERENCED, ER_NO_REFERENCED_ROW */ => DbErrorKind::ForeignKeyViolation{constraint: None},
+            1048 /* ER_BAD_NULL_ERROR */ | 1364 /* ER_NO_DEFAULT_FOR_FIELD */ => {
+                // ie: Column 'name' cannot be null
+                let column = message.split('\'').nth(1).map(|c| c.to_string());
+                DbErrorKind::NotNullViolation{column: column}
+            },
+            1064 /* ER_PARSE_ERROR */ => DbErrorKind::Syntax,
+            1264 | 1292 | 1366 /* out of range, truncated, incorrect value */ => DbErrorKind::TypeConversion,
+            _ => DbErrorKind::from_sqlstate(state, None, None),
+        }
+    }
+
     pub fn with_pooled_connection(pool: MyPool)->Self{
-        Mysql{pool: Some(pool)}
+        Mysql{pool: Some(pool), transaction_conn: RefCell::new(None), transaction_depth: TransactionDepth::new()}
+    }
+    
+    fn get_pool(&self)->Result<&MyPool, DbError>{
+        match self.pool{
+            Some(ref pool) => Ok(pool),
+            None => Err(DbError::with_kind(DbErrorKind::Connection, "No connection for this database")),
+        }
     }
     
-    fn from_rust_type_tosql(types: &Vec<Value>)->Vec< MyValue>{
+    fn from_rust_type_tosql(types: &Vec<Value>)->Result<Vec<MyValue>, DbError>{
         let mut params:Vec<MyValue> = vec![];
         for t in types{
-            match t {
-                &Value::String(ref x) => {
-                    params.push(MyValue::Bytes(x.as_bytes().to_owned()));
+            let param = match *t {
+                Value::Bool(x) => MyValue::Int(if x { 1 } else { 0 }),
+                Value::I8(x) => MyValue::Int(x as i64),
+                Value::I16(x) => MyValue::Int(x as i64),
+                Value::I32(x) => MyValue::Int(x as i64),
+                Value::I64(x) => MyValue::Int(x),
+                Value::U8(x) => MyValue::UInt(x as u64),
+                Value::U16(x) => MyValue::UInt(x as u64),
+                Value::U32(x) => MyValue::UInt(x as u64),
+                Value::U64(x) => MyValue::UInt(x),
+                Value::F32(x) => MyValue::Float(x as f64),
+                Value::F64(x) => MyValue::Float(x),
+                Value::Decimal(ref x) => MyValue::Bytes(x.to_string().into_bytes()),
+                Value::String(ref x) => MyValue::Bytes(x.as_bytes().to_owned()),
+                Value::VecU8(ref x) => MyValue::Bytes(x.clone()),
+                Value::Object(_) => MyValue::Bytes(t.to_json().to_string().into_bytes()),
+                Value::Array(_) => {
+                    return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to bind {:?}, mysql does not support arrays", t)));
+                },
+                Value::Json(ref x) => MyValue::Bytes(x.to_string().into_bytes()),
+                Value::Uuid(ref x) => MyValue::Bytes(x.to_hyphenated_string().into_bytes()),
+                Value::DateTime(ref x) => {
+                    let x = x.naive_utc();
+                    MyValue::Date(x.year() as u16, x.month() as u8, x.day() as u8,
+                        x.hour() as u8, x.minute() as u8, x.second() as u8, x.nanosecond() / 1000)
                 },
-                _ => panic!("not yet here {:?}", t),
+                Value::NaiveDateTime(ref x) => MyValue::Date(x.year() as u16, x.month() as u8, x.day() as u8,
+                        x.hour() as u8, x.minute() as u8, x.second() as u8, x.nanosecond() / 1000),
+                Value::NaiveDate(ref x) => MyValue::Date(x.year() as u16, x.month() as u8, x.day() as u8, 0, 0, 0, 0),
+                Value::NaiveTime(ref x) => MyValue::Time(false, 0, x.hour() as u8, x.minute() as u8, x.second() as u8, x.nanosecond() / 1000),
+                Value::Null => MyValue::NULL,
             };
+            params.push(param);
         }
-        params
+        Ok(params)
     }
     
-        /// convert a record of a row into rust type
-    fn from_sql_to_rust_type(row: &Vec<MyValue>, index:usize)->Value{
-        let value = row.get(index);
-         match value{
-            Some(value) => Value::String(value.into_str()),
-            None => Value::Null,
+    /// convert the value as it is sent by mysql, which is not yet typed by the column
+    fn from_my_value(value: &MyValue, binary: bool)->Value{
+        match *value{
+            MyValue::NULL => Value::Null,
+            MyValue::Int(x) => Value::I64(x),
+            MyValue::UInt(x) => Value::U64(x),
+            MyValue::Float(x) => Value::F64(x),
+            MyValue::Bytes(ref x) => {
+                if binary {
+                    Value::VecU8(x.clone())
+                }else{
+                    Value::String(String::from_utf8_lossy(x).into_owned())
+                }
+            },
+            MyValue::Date(year, month, day, hour, minute, second, micro) => {
+                // zero dates ie: 0000-00-00 is used by mysql in place of null
+                let date = NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32)
+                    .and_then(|d| d.and_hms_micro_opt(hour as u32, minute as u32, second as u32, micro));
+                match date{
+                    Some(date) => Value::NaiveDateTime(date),
+                    None => Value::Null,
+                }
+            },
+            MyValue::Time(negative, days, hour, minute, second, micro) => {
+                let time = NaiveTime::from_hms_micro_opt(hour as u32, minute as u32, second as u32, micro);
+                match time{
+                    // time of the day
+                    Some(time) if !negative && days == 0 => Value::NaiveTime(time),
+                    // a time interval, which does not fit in NaiveTime
+                    _ => Value::String(value.into_str()),
+                }
+            },
         }
     }
+
+    /// convert a record of a row into rust type, using the type of the column in the result set
+    fn from_sql_to_rust_type(column: &MyColumn, value: &MyValue)->Result<Value, DbError>{
+        // the binary character set
+        let binary = column.character_set == 63;
+        let unsigned = column.flags.contains(UNSIGNED_FLAG);
+        let value = Mysql::from_my_value(value, binary);
+        if value == Value::Null{
+            return Ok(value);
+        }
+        let value = match column.column_type{
+            // TINYINT(1) is how mysql declares BOOLEAN
+            ColumnType::MYSQL_TYPE_TINY if column.column_length == 1 => {
+                let x: i64 = try!(FromValue::from_type(value));
+                Value::Bool(x != 0)
+            },
+            ColumnType::MYSQL_TYPE_TINY if unsigned => Value::U8(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_TINY => Value::I8(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_SHORT | ColumnType::MYSQL_TYPE_YEAR if unsigned => Value::U16(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_SHORT | ColumnType::MYSQL_TYPE_YEAR => Value::I16(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_LONG | ColumnType::MYSQL_TYPE_INT24 if unsigned => Value::U32(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_LONG | ColumnType::MYSQL_TYPE_INT24 => Value::I32(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_LONGLONG if unsigned => Value::U64(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_LONGLONG => Value::I64(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_FLOAT => Value::F32(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_DOUBLE => Value::F64(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => Value::Decimal(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_DATETIME | ColumnType::MYSQL_TYPE_TIMESTAMP => Value::NaiveDateTime(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_DATE => match value{
+                Value::NaiveDateTime(x) => Value::NaiveDate(x.date()),
+                _ => Value::NaiveDate(try!(FromValue::from_type(value))),
+            },
+            ColumnType::MYSQL_TYPE_TIME => Value::NaiveTime(try!(FromValue::from_type(value))),
+            ColumnType::MYSQL_TYPE_BIT => value,
+            _ if column.column_type as u8 == MYSQL_TYPE_JSON => Value::Json(try!(FromValue::from_type(value))),
+            // the text and blob types
+            _ => value,
+        };
+        Ok(value)
+    }
     
     ///
     /// convert rust data type names to database data type names
     /// will be used in generating SQL for table creation
     /// FIXME, need to restore the exact data type as before
-    fn rust_type_to_dbtype(&self, rust_type: &str)->String{
+    fn rust_type_to_dbtype(&self, rust_type: &str)->Result<String, DbError>{
 
         let rust_type = match rust_type{
             "bool" => {
@@ -74,7 +226,7 @@ impl Mysql{
                 "integer".to_string()
             },
             "i64"  => {
-                "integer".to_string()
+                "bigint".to_string()
             },
             "f32" => {
                 "real".to_string()
@@ -82,6 +234,10 @@ impl Mysql{
             "f64" => {
                 "real".to_string()
             },
+            // the default of decimal is decimal(10,0), which would drop the fraction
+            "Decimal" => {
+                "decimal(65,30)".to_string()
+            },
             "String" =>{
                 "text".to_string()
             },
@@ -89,152 +245,165 @@ impl Mysql{
                 "blob".to_string()
             },
             "Json" => {
-                "text".to_string()
+                "json".to_string()
             },
             "Uuid" => {
                 "varchar(36)".to_string()
             },
             "NaiveDateTime" => {
-                "numeric".to_string()
+                "datetime".to_string()
             },
             "DateTime<UTC>" => {
-                "numeric".to_string()
+                "datetime".to_string()
             },
             "NaiveDate" => {
-                "numeric".to_string()
+                "date".to_string()
             },
             "NaiveTime" => {
-                "numeric".to_string()
+                "time".to_string()
             },
             "HashMap<String, Option<String>>" => {
                 "text".to_string()
             },
-            _ => panic!("Unable to get the equivalent database data type for {}", rust_type),
+            _ => return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to get the equivalent database data type for {}", rust_type))),
         };
-        rust_type
+        Ok(rust_type)
 
     }
    
-    fn get_prepared_statement<'a>(&'a self, sql: &'a str) -> MyResult<Stmt> { 
-        self.pool.as_ref().unwrap().prepare(sql)
+    /// prepare the statement on the connection of the transaction if there is one
+    fn get_prepared_statement<'a>(&'a self, conn: &'a mut Option<MyPooledConn>, sql: &'a str) -> Result<Stmt<'a>, DbError> { 
+        let stmt = match *conn{
+            Some(ref mut conn) => try!(conn.prepare(sql)),
+            None => try!(try!(self.get_pool()).prepare(sql)),
+        };
+        Ok(stmt)
+    }
+
+    /// run the transaction statement on the held connection
+    fn transaction_query(&self, sql:&str)->Result<(), DbError>{
+        let mut conn = self.transaction_conn.borrow_mut();
+        match *conn{
+            Some(ref mut conn) => {
+                match conn.query(sql){
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(DbError::from(e)),
+                }
+            },
+            None => Err(DbError::new("There is no connection held for the transaction")),
+        }
     }
 }
 
 impl Database for Mysql{
-    fn version(&self)->String{
-       let sql = "select version()";
-       let dao = self.execute_sql_with_return(sql, &vec![]);
-       match dao{
-           Ok(dao) => {
-               if dao.len() == 1{
-                   dao[0].get("version")
-               }else{
-                   panic!("More than 1 rows returned")
-               }
-           },
-           Err(_) => panic!("unable to get database version")
-       }
+    fn version(&self)->Result<String, DbError>{
+       let sql = "select version() as version";
+       let dao = try!(self.execute_sql_with_one_return(sql, &vec![]));
+       dao.try_get("version")
     }
-    fn begin(&self){}
-    fn commit(&self){}
-    fn rollback(&self){}
-    fn is_transacted(&self)->bool{false}
+    fn begin(&self)->Result<(), DbError>{
+        let outermost = self.transaction_depth.get() == 0;
+        if outermost{
+            match try!(self.get_pool()).get_conn(){
+                Ok(conn) => *self.transaction_conn.borrow_mut() = Some(conn),
+                Err(e) => return Err(DbError::from(e)),
+            };
+        }
+        let result = self.transaction_depth.begin(|sql| self.transaction_query(sql));
+        if result.is_err() && outermost{
+            self.transaction_conn.borrow_mut().take();
+        }
+        result
+    }
+    /// the connection is released back to the pool when the outermost transaction ends
+    fn commit(&self)->Result<(), DbError>{
+        let result = self.transaction_depth.commit(|sql| self.transaction_query(sql));
+        if self.transaction_depth.get() == 0{
+            self.transaction_conn.borrow_mut().take();
+        }
+        result
+    }
+    fn rollback(&self)->Result<(), DbError>{
+        let result = self.transaction_depth.rollback(|sql| self.transaction_query(sql));
+        if self.transaction_depth.get() == 0{
+            self.transaction_conn.borrow_mut().take();
+        }
+        result
+    }
+    fn is_transacted(&self)->bool{self.transaction_depth.get() > 0}
     fn is_closed(&self)->bool{false}
     fn is_connected(&self)->bool{false}
-    fn close(&self){}
+    fn close(&self)->Result<(), DbError>{Ok(())}
     fn is_valid(&self)->bool{false}
-    fn reset(&self){}
+    fn reset(&self)->Result<(), DbError>{Ok(())}
     
     /// return this list of options, supported features in the database
     fn sql_options(&self)->Vec<SqlOption>{
         vec![
             SqlOption::UsesQuestionMark,//mysql uses question mark instead of the numbered params
+            SqlOption::SupportsOnDuplicateKey,
+            SqlOption::UsesNullSafeEqual,
         ]
     }
-    
-    fn update(&self, query:&Query)->Dao{panic!("not yet")}
-    fn delete(&self, query:&Query)->Result<usize, String>{panic!("not yet");}
+
+    /// the prepared statement protocol uses 16 bit for the number of placeholders
+    fn max_parameters(&self)->usize{
+        65535
+    }
     
     fn execute_sql_with_return(&self, sql:&str, params:&Vec<Value>)->Result<Vec<Dao>, DbError>{
         println!("SQL: \n{}", sql);
         println!("param: {:?}", params);
-        assert!(self.pool.is_some());
-        let mut stmt = self.get_prepared_statement(sql).unwrap();
-        let mut columns = vec![];
-        for col in stmt.columns_ref().unwrap(){
-            let column_name = String::from_utf8(col.name.clone()).unwrap();
-            columns.push(column_name);
-        }
+        let mut conn = self.transaction_conn.borrow_mut();
+        let mut stmt = try!(self.get_prepared_statement(&mut conn, sql));
+        let columns:Vec<MyColumn> = match stmt.columns_ref(){
+            Some(cols) => cols.to_vec(),
+            None => vec![],
+        };
         let mut daos = vec![];
-        let param = Mysql::from_rust_type_tosql(params);
-        let rows = stmt.execute(&param);
-        match rows{
-            Ok(rows) => 
-            for row in rows {
-                match row{
-                    Ok(row) => {
-                        let mut index = 0;
-                        let mut dao = Dao::new();
-                        for col in &columns{
-                            let rtype = Mysql::from_sql_to_rust_type(&row, index);
-                            dao.set_value(col, rtype);
-                            index += 1;
-                        }
-                        daos.push(dao);
-                    }
-                    Err(e) => {
-                        println!("error! {}",e) 
-                    }
-                }
-            },
-            Err(e) => println!("Something is wrong")
+        let param = try!(Mysql::from_rust_type_tosql(params));
+        let rows = try!(stmt.execute(&param));
+        for row in rows {
+            let row = try!(row);
+            let mut dao = Dao::new();
+            for (col, value) in columns.iter().zip(row.iter()){
+                let column_name = String::from_utf8_lossy(&col.name).into_owned();
+                let rtype = try!(Mysql::from_sql_to_rust_type(col, value));
+                dao.set_value(&column_name, rtype);
+            }
+            daos.push(dao);
         }
         Ok(daos)
     }
     
-    fn execute_sql_with_one_return(&self, sql:&str, params:&Vec<Value>)->Result<Dao, DbError>{
-        let dao = self.execute_sql_with_return(sql, params);
-        match dao{
-            Ok(dao) => {
-                if dao.len() == 1{
-                    Ok(dao[0].clone())
-                }else{
-                    Err(DbError::new("There should be 1 and only 1 record return here"))
-                }
-            },
-            Err(_) =>  Err(DbError::new("Error in the query"))
-        }
-    }
-    
     /// generic execute sql which returns not much information,
     /// returns only the number of affected records or errors
     /// can be used with DDL operations (CREATE, DELETE, ALTER, DROP)
     fn execute_sql(&self, sql:&str, params:&Vec<Value>)->Result<usize, DbError>{
         println!("SQL: \n{}", sql);
         println!("param: {:?}", params);
-        let to_sql_types = Mysql::from_rust_type_tosql(params);
-        assert!(self.pool.is_some());
-        let result = self.pool.as_ref().unwrap().prep_exec(sql, &to_sql_types);
-        match result{
-            Ok(x) => { Ok(x.affected_rows() as usize)},
-            Err(e) => {
-                Err(DbError::new("Something is wrong")) 
-            }
-        }
+        let to_sql_types = try!(Mysql::from_rust_type_tosql(params));
+        let mut conn = self.transaction_conn.borrow_mut();
+        let result = match *conn{
+            Some(ref mut conn) => conn.prep_exec(sql, &to_sql_types),
+            None => try!(self.get_pool()).prep_exec(sql, &to_sql_types),
+        };
+        let result = try!(result);
+        Ok(result.affected_rows() as usize)
     }
 
 }
 
 impl DatabaseDDL for Mysql{
-    fn create_schema(&self, schema:&str){
-        panic!("mysql does not support schema")
+    fn create_schema(&self, schema:&str)->Result<(), DbError>{
+        Err(DbError::new("mysql does not support schema"))
     }
 
-    fn drop_schema(&self, schema:&str){
-        panic!("mysql does not support schema")
+    fn drop_schema(&self, schema:&str)->Result<(), DbError>{
+        Err(DbError::new("mysql does not support schema"))
     }
     
-    fn build_create_table(&self, table:&Table)->SqlFrag{
+    fn build_create_table(&self, table:&Table)->Result<SqlFrag, DbError>{
         let mut w = SqlFrag::new(self.sql_options());
         w.append("CREATE TABLE ");
         w.append(&table.name);
@@ -245,37 +414,35 @@ impl DatabaseDDL for Mysql{
             if do_comma {w.commasp();}else {do_comma=true;}
             w.append(&c.name);
             w.append(" ");
-            let dt = self.rust_type_to_dbtype(&c.data_type);
+            let dt = try!(self.rust_type_to_dbtype(&c.data_type));
             w.append(&dt);
             if c.is_primary {
                 w.append(" PRIMARY KEY ");
             }
         }
         w.append(")");
-        w
+        Ok(w)
     }
-    fn create_table(&self, table:&Table){
-         let frag = self.build_create_table(table);
-         match self.execute_sql(&frag.sql, &vec![]){
-            Ok(x) => println!("created table.."),
-            Err(e) => panic!("table not created {}", e),
-         }
+    fn create_table(&self, table:&Table)->Result<(), DbError>{
+         let frag = try!(self.build_create_table(table));
+         try!(self.execute_sql(&frag.sql, &vec![]));
+         Ok(())
     }
 
-    fn rename_table(&self, table:&Table, new_tablename:String){
-        
+    fn rename_table(&self, table:&Table, new_tablename:String)->Result<(), DbError>{
+        Err(DbError::new("Renaming table is not yet supported in mysql"))
     }
 
-    fn drop_table(&self, table:&Table){
-        panic!("not yet");
+    fn drop_table(&self, table:&Table)->Result<(), DbError>{
+        Err(DbError::new("Dropping table is not yet supported in mysql"))
     }
 
-    fn set_foreign_constraint(&self, model:&Table){
-        panic!("not yet");
+    fn set_foreign_constraint(&self, model:&Table)->Result<(), DbError>{
+        Err(DbError::new("Setting the foreign constraint is not yet supported"))
     }
 
-    fn set_primary_constraint(&self, model:&Table){
-        panic!("not yet");
+    fn set_primary_constraint(&self, model:&Table)->Result<(), DbError>{
+        Err(DbError::new("Setting the primary constraint is not yet supported"))
     }
 }
 
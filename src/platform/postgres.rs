@@ -4,19 +4,517 @@ use dao::Dao;
 
 use postgres::Connection;
 use regex::Regex;
-use dao::Value;
-use database::{Database, DatabaseDev, DatabaseDDL, DbError};
+use dao::{Value, Decimal};
+use database::{Database, DatabaseDev, DatabaseDDL, DbError, DbErrorKind};
 use postgres::types::Type;
-use postgres::types::ToSql;
+use postgres::error::Error as PgError;
+use postgres::types::{ToSql, FromSql, IsNull, Kind, SessionInfo};
+use postgres::Result as PgResult;
 use writer::SqlFrag;
 use postgres::rows::Row;
 use database::SqlOption;
+use database::TransactionDepth;
 use r2d2::PooledConnection;
 use r2d2_postgres::PostgresConnectionManager;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use uuid::Uuid;
+use std::mem;
+use std::fmt;
+use std::iter;
+use std::f64;
+use std::str::FromStr;
+use chrono::Duration;
+use chrono::datetime::DateTime;
+use chrono::naive::date::NaiveDate;
+use chrono::naive::time::NaiveTime;
+use chrono::offset::utc::UTC;
+use rustc_serialize::json::{Json, ToJson};
 
 pub struct Postgres{
     /// a connection pool is provided
     pub pool: Option<PooledConnection<PostgresConnectionManager>>,
+    /// the number of nested transactions in progress on this connection
+    transaction_depth: TransactionDepth,
+}
+
+/// map the error from the driver to the kind of database error through its SQLSTATE,
+/// the original error is kept as the cause
+impl From<PgError> for DbError{
+
+    fn from(e: PgError)->Self{
+        let kind = match e{
+            PgError::Db(ref db_error) => {
+                let constraint = db_error.constraint().map(|c| c.to_string());
+                let column = db_error.column().map(|c| c.to_string());
+                DbErrorKind::from_sqlstate(db_error.code().code(), constraint, column)
+            },
+            PgError::Io(_) => DbErrorKind::Connection,
+            PgError::Conversion(_) => DbErrorKind::TypeConversion,
+        };
+        DbError::from_driver(kind, e)
+    }
+}
+
+/// NULL of any type, the type is set by the server when the statement is prepared
+struct TypedNull;
+
+impl ToSql for TypedNull{
+    fn to_sql<W: Write + ?Sized>(&self, _: &Type, _: &mut W, _: &SessionInfo)->PgResult<IsNull>{
+        Ok(IsNull::Yes)
+    }
+
+    fn accepts(_: &Type)->bool{
+        true
+    }
+
+    to_sql_checked!();
+}
+
+/// numeric is sent as base 10000 digits, see decode_numeric
+impl ToSql for Decimal{
+    fn to_sql<W: Write + ?Sized>(&self, _: &Type, out: &mut W, _: &SessionInfo)->PgResult<IsNull>{
+        try!(out.write_all(&encode_numeric(self)));
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type)->bool{
+        match *ty{
+            Type::Numeric => true,
+            _ => false,
+        }
+    }
+
+    to_sql_checked!();
+}
+
+/// the json text of a Json value, jsonb is prefixed with the version of its format
+struct JsonParam(String);
+
+impl ToSql for JsonParam{
+    fn to_sql<W: Write + ?Sized>(&self, ty: &Type, out: &mut W, _: &SessionInfo)->PgResult<IsNull>{
+        if let Type::Jsonb = *ty{
+            try!(out.write_all(&[1]));
+        }
+        try!(out.write_all(self.0.as_bytes()));
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type)->bool{
+        match *ty{
+            Type::Json | Type::Jsonb => true,
+            _ => false,
+        }
+    }
+
+    to_sql_checked!();
+}
+
+/// one dimensional array, the elements are written as the element type of the target array type
+struct ArrayParam(Vec<Box<ToSql>>);
+
+impl fmt::Debug for ArrayParam{
+    fn fmt(&self, f: &mut fmt::Formatter)->fmt::Result{
+        write!(f, "ArrayParam({} elements)", self.0.len())
+    }
+}
+
+impl ToSql for ArrayParam{
+    fn to_sql<W: Write + ?Sized>(&self, ty: &Type, out: &mut W, ctx: &SessionInfo)->PgResult<IsNull>{
+        let member = match *ty.kind(){
+            Kind::Array(ref member) => member,
+            _ => unreachable!(),
+        };
+        let mut elements = vec![];
+        let mut has_null = 0;
+        for element in &self.0{
+            let mut buf: Vec<u8> = vec![];
+            match try!(element.to_sql_checked(member, &mut buf, ctx)){
+                IsNull::Yes => {
+                    has_null = 1;
+                    elements.extend(&write_i32(-1));
+                },
+                IsNull::No => {
+                    elements.extend(&write_i32(buf.len() as i32));
+                    elements.extend(&buf);
+                },
+            }
+        }
+        let ndim = if self.0.is_empty() { 0 } else { 1 };
+        try!(out.write_all(&write_i32(ndim)));
+        try!(out.write_all(&write_i32(has_null)));
+        try!(out.write_all(&write_i32(member.oid() as i32)));
+        if ndim == 1{
+            try!(out.write_all(&write_i32(self.0.len() as i32)));
+            // the lower bound of the index
+            try!(out.write_all(&write_i32(1)));
+        }
+        try!(out.write_all(&elements));
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type)->bool{
+        match *ty.kind(){
+            Kind::Array(_) => true,
+            _ => false,
+        }
+    }
+
+    to_sql_checked!();
+}
+
+fn write_i32(value: i32)->[u8; 4]{
+    [(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]
+}
+
+/// the value of any type, as it is sent by the server in binary format,
+/// used for decoding the types which has no conversion in the driver
+struct RawValue(Vec<u8>);
+
+impl FromSql for RawValue{
+    fn from_sql<R: Read>(_: &Type, raw: &mut R, _: &SessionInfo)->PgResult<Self>{
+        let mut buf = vec![];
+        try!(raw.read_to_end(&mut buf));
+        Ok(RawValue(buf))
+    }
+
+    fn accepts(_: &Type)->bool{
+        true
+    }
+}
+
+fn malformed(type_name: &str)->DbError{
+    DbError::with_kind(DbErrorKind::TypeConversion, &format!("Malformed {} value from the database", type_name))
+}
+
+/// read big endian integers from the front of the buffer, advancing it
+fn read_be(buf: &mut &[u8], size: usize, type_name: &str)->Result<u64, DbError>{
+    if buf.len() < size{
+        return Err(malformed(type_name));
+    }
+    let mut value:u64 = 0;
+    for b in &buf[..size]{
+        value = (value << 8) | *b as u64;
+    }
+    *buf = &buf[size..];
+    Ok(value)
+}
+
+fn read_i16(buf: &mut &[u8], type_name: &str)->Result<i16, DbError>{
+    Ok(try!(read_be(buf, 2, type_name)) as u16 as i16)
+}
+
+fn read_i32(buf: &mut &[u8], type_name: &str)->Result<i32, DbError>{
+    Ok(try!(read_be(buf, 4, type_name)) as u32 as i32)
+}
+
+fn read_i64(buf: &mut &[u8], type_name: &str)->Result<i64, DbError>{
+    Ok(try!(read_be(buf, 8, type_name)) as i64)
+}
+
+/// numeric is sent as base 10000 digits, converted into its exact decimal text
+fn decode_numeric(raw: &[u8])->Result<String, DbError>{
+    let mut buf = raw;
+    let ndigits = try!(read_i16(&mut buf, "numeric")) as i32;
+    let weight = try!(read_i16(&mut buf, "numeric")) as i32;
+    let sign = try!(read_i16(&mut buf, "numeric")) as u16;
+    let dscale = try!(read_i16(&mut buf, "numeric")) as usize;
+    let mut digits = vec![];
+    for _ in 0..ndigits{
+        digits.push(try!(read_i16(&mut buf, "numeric")));
+    }
+    if sign == 0xC000{
+        return Ok("NaN".to_string());
+    }
+    let digit = |i: i32| if i >= 0 && i < ndigits { digits[i as usize] } else { 0 };
+    let mut s = String::new();
+    if sign == 0x4000{
+        s.push('-');
+    }
+    if weight < 0{
+        s.push('0');
+    }else{
+        s.push_str(&format!("{}", digit(0)));
+        for i in 1..(weight + 1){
+            s.push_str(&format!("{:04}", digit(i)));
+        }
+    }
+    if dscale > 0{
+        let mut fraction = String::new();
+        let mut i = weight + 1;
+        while fraction.len() < dscale{
+            fraction.push_str(&format!("{:04}", digit(i)));
+            i += 1;
+        }
+        fraction.truncate(dscale);
+        s.push('.');
+        s.push_str(&fraction);
+    }
+    Ok(s)
+}
+
+/// the numeric value of the decoded text, NaN has no Decimal equivalent
+fn numeric_value(raw: &[u8])->Result<Value, DbError>{
+    let text = try!(decode_numeric(raw));
+    if text == "NaN"{
+        return Ok(Value::F64(f64::NAN));
+    }
+    Ok(Value::Decimal(try!(Decimal::from_str(&text))))
+}
+
+/// the decimal as base 10000 digits, the integer part is grouped from the decimal point to the left,
+/// the fraction part from the decimal point to the right
+fn encode_numeric(decimal: &Decimal)->Vec<u8>{
+    let (integer, fraction) = decimal.parts();
+    let integer = integer.trim_left_matches('0');
+    let left_pad = (4 - integer.len() % 4) % 4;
+    let right_pad = (4 - fraction.len() % 4) % 4;
+    let padded: String = iter::repeat('0').take(left_pad)
+        .chain(integer.chars())
+        .chain(fraction.chars())
+        .chain(iter::repeat('0').take(right_pad))
+        .collect();
+    let mut groups: Vec<i16> = padded.as_bytes().chunks(4)
+        .map(|c| c.iter().fold(0i16, |acc, d| acc * 10 + (d - b'0') as i16))
+        .collect();
+    let mut weight = ((integer.len() + left_pad) / 4) as i16 - 1;
+    while !groups.is_empty() && groups[0] == 0{
+        groups.remove(0);
+        weight -= 1;
+    }
+    while !groups.is_empty() && groups[groups.len() - 1] == 0{
+        groups.pop();
+    }
+    if groups.is_empty(){
+        weight = 0;
+    }
+    let sign: u16 = if decimal.is_negative() { 0x4000 } else { 0 };
+    let mut buf = vec![];
+    for value in [groups.len() as i16, weight, sign as i16, decimal.scale() as i16].iter().chain(groups.iter()){
+        buf.push((*value >> 8) as u8);
+        buf.push(*value as u8);
+    }
+    buf
+}
+
+/// interval is sent as microseconds, days and months,
+/// converted into the text postgresql displays ie: `1 year 2 mons 3 days 04:05:06`
+fn decode_interval(raw: &[u8])->Result<String, DbError>{
+    let mut buf = raw;
+    let micros = try!(read_i64(&mut buf, "interval"));
+    let days = try!(read_i32(&mut buf, "interval"));
+    let months = try!(read_i32(&mut buf, "interval"));
+    fn unit(value: i64, singular: &str, plural: &str)->String{
+        format!("{} {}", value, if value == 1 || value == -1 { singular } else { plural })
+    }
+    let mut parts = vec![];
+    let (years, months) = (months as i64 / 12, months as i64 % 12);
+    if years != 0 { parts.push(unit(years, "year", "years")); }
+    if months != 0 { parts.push(unit(months, "mon", "mons")); }
+    if days != 0 { parts.push(unit(days as i64, "day", "days")); }
+    if micros != 0 || parts.is_empty(){
+        let sign = if micros < 0 { "-" } else { "" };
+        let micros = micros.abs();
+        let secs = micros / 1000000;
+        let mut time = format!("{}{:02}:{:02}:{:02}", sign, secs / 3600, secs / 60 % 60, secs % 60);
+        if micros % 1000000 != 0{
+            time.push_str(&format!(".{:06}", micros % 1000000));
+        }
+        parts.push(time);
+    }
+    Ok(parts.join(" "))
+}
+
+/// time with time zone is sent as microseconds and the zone in seconds west of UTC,
+/// converted into the time in UTC
+fn decode_timetz(raw: &[u8])->Result<NaiveTime, DbError>{
+    let mut buf = raw;
+    let micros = try!(read_i64(&mut buf, "timetz"));
+    let zone = try!(read_i32(&mut buf, "timetz")) as i64;
+    let secs = ((micros / 1000000 + zone) % 86400 + 86400) % 86400;
+    let nanos = (micros % 1000000 * 1000) as u32;
+    match NaiveTime::from_num_seconds_from_midnight_opt(secs as u32, nanos){
+        Some(time) => Ok(time),
+        None => Err(malformed("timetz")),
+    }
+}
+
+/// inet and cidr are sent as the address family, the netmask bits and the address bytes
+fn decode_inet(raw: &[u8])->Result<String, DbError>{
+    if raw.len() < 4 || raw.len() != 4 + raw[3] as usize{
+        return Err(malformed("inet"));
+    }
+    let (family, bits, is_cidr, addr) = (raw[0], raw[1], raw[2], &raw[4..]);
+    let (mut s, max_bits) = match (family, addr.len()){
+        (2, 4) => (addr.iter().map(|b| b.to_string()).collect::<Vec<String>>().join("."), 32),
+        (3, 16) => (addr.chunks(2).map(|c| format!("{:x}", (c[0] as u16) << 8 | c[1] as u16))
+                        .collect::<Vec<String>>().join(":"), 128),
+        _ => return Err(malformed("inet")),
+    };
+    if is_cidr != 0 || bits != max_bits{
+        s.push_str(&format!("/{}", bits));
+    }
+    Ok(s)
+}
+
+/// tsvector is sent as the lexemes with their positions and weights,
+/// converted into the text postgresql displays ie: `'fat':2A 'rat':3`
+fn decode_tsvector(raw: &[u8])->Result<String, DbError>{
+    let mut buf = raw;
+    let count = try!(read_i32(&mut buf, "tsvector"));
+    let mut lexemes = vec![];
+    for _ in 0..count{
+        let end = match buf.iter().position(|b| *b == 0){
+            Some(end) => end,
+            None => return Err(malformed("tsvector")),
+        };
+        let mut lexeme = format!("'{}'", String::from_utf8_lossy(&buf[..end]).replace("'", "''"));
+        buf = &buf[end + 1..];
+        let npos = try!(read_be(&mut buf, 2, "tsvector"));
+        let mut positions = vec![];
+        for _ in 0..npos{
+            let pos = try!(read_be(&mut buf, 2, "tsvector"));
+            let weight = match pos >> 14 { 3 => "A", 2 => "B", 1 => "C", _ => "" };
+            positions.push(format!("{}{}", pos & 0x3fff, weight));
+        }
+        if !positions.is_empty(){
+            lexeme.push(':');
+            lexeme.push_str(&positions.join(","));
+        }
+        lexemes.push(lexeme);
+    }
+    Ok(lexemes.join(" "))
+}
+
+/// hstore is sent as the number of pairs, then the length prefixed keys and values,
+/// a null value has the length of -1
+fn decode_hstore(raw: &[u8])->Result<BTreeMap<String, Value>, DbError>{
+    let mut buf = raw;
+    let count = try!(read_i32(&mut buf, "hstore"));
+    let mut map = BTreeMap::new();
+    for _ in 0..count{
+        let key = match try!(read_text(&mut buf, "hstore")){
+            Some(key) => key,
+            None => return Err(malformed("hstore")),
+        };
+        let value = match try!(read_text(&mut buf, "hstore")){
+            Some(value) => Value::String(value),
+            None => Value::Null,
+        };
+        map.insert(key, value);
+    }
+    Ok(map)
+}
+
+/// read a length prefixed value, None when the length is -1
+fn read_bytes<'a>(buf: &mut &'a [u8], type_name: &str)->Result<Option<&'a [u8]>, DbError>{
+    let len = try!(read_i32(buf, type_name));
+    if len < 0{
+        return Ok(None);
+    }
+    let len = len as usize;
+    if buf.len() < len{
+        return Err(malformed(type_name));
+    }
+    let bytes = &buf[..len];
+    *buf = &buf[len..];
+    Ok(Some(bytes))
+}
+
+fn read_text(buf: &mut &[u8], type_name: &str)->Result<Option<String>, DbError>{
+    let bytes = try!(read_bytes(buf, type_name));
+    Ok(bytes.map(|b| String::from_utf8_lossy(b).into_owned()))
+}
+
+/// decode an element of an array of this type
+fn decode_array_element(array_type: &Type, raw: &[u8])->Result<Value, DbError>{
+    let mut buf = raw;
+    let value = match *array_type{
+        Type::BoolArray => Value::Bool(try!(read_be(&mut buf, 1, "bool[]")) != 0),
+        Type::Int2Array => Value::I16(try!(read_i16(&mut buf, "smallint[]"))),
+        Type::Int4Array => Value::I32(try!(read_i32(&mut buf, "integer[]"))),
+        Type::Int8Array => Value::I64(try!(read_i64(&mut buf, "bigint[]"))),
+        Type::Float4Array => {
+            let bits = try!(read_be(&mut buf, 4, "real[]")) as u32;
+            Value::F32(unsafe{ mem::transmute::<u32, f32>(bits) })
+        },
+        Type::Float8Array => {
+            let bits = try!(read_be(&mut buf, 8, "double precision[]"));
+            Value::F64(unsafe{ mem::transmute::<u64, f64>(bits) })
+        },
+        Type::NumericArray => try!(numeric_value(raw)),
+        Type::UuidArray => match Uuid::from_bytes(raw){
+            Ok(uuid) => Value::Uuid(uuid),
+            Err(_) => return Err(malformed("uuid[]")),
+        },
+        // dates and timestamps are counted from 2000-01-01
+        Type::DateArray => {
+            let days = try!(read_i32(&mut buf, "date[]"));
+            Value::NaiveDate(NaiveDate::from_ymd(2000, 1, 1) + Duration::days(days as i64))
+        },
+        Type::TimestampArray | Type::TimestampTZArray => {
+            let micros = try!(read_i64(&mut buf, "timestamp[]"));
+            let timestamp = NaiveDate::from_ymd(2000, 1, 1).and_hms(0, 0, 0) + Duration::microseconds(micros);
+            match *array_type{
+                Type::TimestampTZArray => Value::DateTime(DateTime::from_utc(timestamp, UTC)),
+                _ => Value::NaiveDateTime(timestamp),
+            }
+        },
+        Type::TimeArray => {
+            let micros = try!(read_i64(&mut buf, "time[]"));
+            Value::NaiveTime(NaiveTime::from_hms(0, 0, 0) + Duration::microseconds(micros))
+        },
+        Type::InetArray | Type::CidrArray => Value::String(try!(decode_inet(raw))),
+        Type::JsonArray => Value::Json(try!(parse_json(&String::from_utf8_lossy(raw)))),
+        Type::JsonbArray if !raw.is_empty() => Value::Json(try!(parse_json(&String::from_utf8_lossy(&raw[1..])))),
+        // text, varchar, bpchar and name are sent as text
+        _ => Value::String(String::from_utf8_lossy(raw).into_owned()),
+    };
+    Ok(value)
+}
+
+/// arrays are sent as the dimensions, then the length prefixed elements,
+/// only one dimensional arrays are supported
+fn decode_array(array_type: &Type, raw: &[u8])->Result<Vec<Value>, DbError>{
+    let mut buf = raw;
+    let ndim = try!(read_i32(&mut buf, "array"));
+    let _has_null = try!(read_i32(&mut buf, "array"));
+    let _element_oid = try!(read_i32(&mut buf, "array"));
+    if ndim > 1{
+        return Err(DbError::with_kind(DbErrorKind::TypeConversion, "Multi dimensional arrays are not supported"));
+    }
+    let mut count = 0;
+    if ndim == 1{
+        count = try!(read_i32(&mut buf, "array"));
+        let _lower_bound = try!(read_i32(&mut buf, "array"));
+    }
+    let mut elements = vec![];
+    for _ in 0..count{
+        let element = match try!(read_bytes(&mut buf, "array")){
+            Some(bytes) => try!(decode_array_element(array_type, bytes)),
+            None => Value::Null,
+        };
+        elements.push(element);
+    }
+    Ok(elements)
+}
+
+fn is_array(dtype: &Type)->bool{
+    match *dtype{
+        Type::BoolArray | Type::NameArray | Type::Int2Array | Type::Int4Array | Type::Int8Array
+        | Type::TextArray | Type::VarcharArray | Type::BpcharArray | Type::Float4Array
+        | Type::Float8Array | Type::UuidArray | Type::NumericArray | Type::DateArray
+        | Type::TimeArray | Type::TimestampArray | Type::TimestampTZArray
+        | Type::InetArray | Type::CidrArray | Type::JsonArray | Type::JsonbArray => true,
+        _ => false,
+    }
+}
+
+fn parse_json(text: &str)->Result<Json, DbError>{
+    match Json::from_str(text){
+        Ok(json) => Ok(json),
+        Err(e) => Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to parse json: {}", e))),
+    }
 }
 
 /// Build the Query into a SQL statements that is a valid
@@ -29,22 +527,20 @@ impl Postgres{
     /// useful when just building sql queries specific to this platform
     /// inexpensive operation, so can have multiple instances
     pub fn new()->Self{
-        Postgres{pool: None}
+        Postgres{pool: None, transaction_depth: TransactionDepth::new()}
     }
     
     
     pub fn with_pooled_connection(pool: PooledConnection<PostgresConnectionManager>)->Self{
-       Postgres{pool: Some(pool)}
+       Postgres{pool: Some(pool), transaction_depth: TransactionDepth::new()}
     }
     
     
     
-    pub fn get_connection(&self)->&Connection{
-       if self.pool.is_some(){
-            &self.pool.as_ref().unwrap()
-        }
-        else{
-            panic!("No connection for this database")
+    pub fn get_connection(&self)->Result<&Connection, DbError>{
+        match self.pool{
+            Some(ref pool) => Ok(&**pool),
+            None => Err(DbError::with_kind(DbErrorKind::Connection, "No connection for this database")),
         }
     }
 
@@ -53,152 +549,120 @@ impl Postgres{
     /// TODO: put this somewhere organized
     /// TODO: match all the other filter types
     /// TODO: need to have a container for PgType contained before being borrowed to actual postgres type
-    fn from_rust_type_tosql<'b>(&self, types: &'b Vec<Value>)->Vec<&'b ToSql>{
-        let mut params:Vec<&ToSql> = vec![];
+    /// convert the values into the types postgresql can bind,
+    /// unsigned integers are widened to the next signed type since postgresql has no unsigned types
+    fn from_rust_type_tosql(&self, types: &Vec<Value>)->Result<Vec<Value>, DbError>{
+        let mut params = vec![];
         for t in types{
-            match *t {
-                Value::Bool(ref x) => params.push(x),
-                Value::I8(ref x) => params.push(x),
-                Value::I16(ref x) => params.push(x),
-                Value::I32(ref x) => params.push(x),
-                Value::I64(ref x) => params.push(x),
-                Value::U8(ref x) => panic!("unsupported/unexpected type! {:?}", t),
-                Value::U16(ref x) => panic!("unsupported/unexpected type! {:?}", t),
-                Value::U32(ref x) => params.push(x),
-                Value::U64(ref x) => panic!("unsupported/unexpected type! {:?}", t),
-                Value::F32(ref x) => params.push(x),
-                Value::F64(ref x) => params.push(x),
-                Value::String(ref x) => params.push(x),
-                Value::VecU8(ref x) => params.push(x),
-                Value::Uuid(ref x) => params.push(x),
-                Value::DateTime(ref x) => params.push(x),
-                Value::NaiveDate(ref x) => params.push(x),
-                Value::NaiveTime(ref x) => params.push(x),
-                Value::NaiveDateTime(ref x) => params.push(x),
-                Value::Null => panic!("unsupported/unexpected type! {:?}", t),
-                _ => panic!("not yet here {:?}", t),
+            let value = match *t {
+                Value::U8(x) => Value::I16(x as i16),
+                Value::U16(x) => Value::I32(x as i32),
+                Value::U32(x) => Value::I64(x as i64),
+                Value::U64(x) => {
+                    if x > i64::max_value() as u64 {
+                        return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to bind {:?}, it does not fit in bigint", t)));
+                    }
+                    Value::I64(x as i64)
+                },
+                Value::Object(_) => Value::Json(t.to_json()),
+                Value::Array(ref x) => Value::Array(try!(self.from_rust_type_tosql(x))),
+                _ => t.clone(),
             };
+            params.push(value);
         }
-        params
+        Ok(params)
+    }
+
+    /// the values converted from from_rust_type_tosql as parameters,
+    /// NULL takes the type of the parameter as inferred by the server, ie: the type of the target column
+    fn as_params(values: &Vec<Value>)->Result<Vec<Box<ToSql>>, DbError>{
+        let mut params:Vec<Box<ToSql>> = vec![];
+        for v in values{
+            match *v {
+                Value::Bool(x) => params.push(Box::new(x)),
+                Value::I8(x) => params.push(Box::new(x)),
+                Value::I16(x) => params.push(Box::new(x)),
+                Value::I32(x) => params.push(Box::new(x)),
+                Value::I64(x) => params.push(Box::new(x)),
+                Value::F32(x) => params.push(Box::new(x)),
+                Value::F64(x) => params.push(Box::new(x)),
+                Value::Decimal(ref x) => params.push(Box::new(x.clone())),
+                Value::String(ref x) => params.push(Box::new(x.clone())),
+                Value::VecU8(ref x) => params.push(Box::new(x.clone())),
+                Value::Json(ref x) => params.push(Box::new(JsonParam(x.to_string()))),
+                Value::Array(ref x) => params.push(Box::new(ArrayParam(try!(Postgres::as_params(x))))),
+                Value::Uuid(ref x) => params.push(Box::new(x.clone())),
+                Value::DateTime(ref x) => params.push(Box::new(x.clone())),
+                Value::NaiveDate(ref x) => params.push(Box::new(x.clone())),
+                Value::NaiveTime(ref x) => params.push(Box::new(x.clone())),
+                Value::NaiveDateTime(ref x) => params.push(Box::new(x.clone())),
+                Value::Null => params.push(Box::new(TypedNull)),
+                Value::U8(_) | Value::U16(_) | Value::U32(_) | Value::U64(_) | Value::Object(_) => {
+                    return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to bind {:?}, it is not converted with from_rust_type_tosql", v)));
+                },
+            };
+        }
+        Ok(params)
     }
     
     /// convert a record of a row into rust type
-    fn from_sql_to_rust_type(&self, dtype:&Type, row: &Row, index:usize)->Value{
-        match *dtype{
-            Type::Uuid => {
-                let value = row.get_opt(index);
-                match value{
-                    Ok(value) => Value::Uuid(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Varchar | Type::Text | Type::Bpchar => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::String(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::TimestampTZ | Type::Timestamp => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::DateTime(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Float4 => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::F32(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Numeric | Type::Float8 => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::F64(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Bool => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::Bool(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Json => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::String(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Int2 => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::I16(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Int4 => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::I32(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Int8 => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::I64(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Timetz => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::DateTime(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Date => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::DateTime(value),
-                    Err(_) => Value::Null,
-                }
-            },
-             Type::Bytea => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::VecU8(value),
-                    Err(_) => Value::Null,
-                }
-            },
-             Type::Inet => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::String(value),
-                    Err(_) => Value::Null,
-                }
-            },
-            Type::Tsvector => {
-                let value = row.get_opt(index);
-                 match value{
-                    Ok(value) => Value::String(value),
-                    Err(_) => Value::Null,
+    /// the types which has a conversion in the driver are read directly,
+    /// the rest are decoded from the binary format, types that are not known are read as text
+    fn from_sql_to_rust_type(&self, dtype:&Type, row: &Row, index:usize)->Result<Value, DbError>{
+        fn get<T: FromSql>(row: &Row, index: usize, variant: fn(T)->Value)->Value{
+            match row.get_opt(index){
+                Ok(value) => variant(value),
+                Err(_) => Value::Null,
+            }
+        }
+        let value = match *dtype{
+            Type::Uuid => get(row, index, Value::Uuid),
+            Type::Varchar | Type::Text | Type::Bpchar | Type::Name => get(row, index, Value::String),
+            Type::TimestampTZ => get(row, index, Value::DateTime),
+            Type::Timestamp => get(row, index, Value::NaiveDateTime),
+            Type::Date => get(row, index, Value::NaiveDate),
+            Type::Time => get(row, index, Value::NaiveTime),
+            Type::Float4 => get(row, index, Value::F32),
+            Type::Float8 => get(row, index, Value::F64),
+            Type::Bool => get(row, index, Value::Bool),
+            Type::Char => get(row, index, Value::I8),
+            Type::Int2 => get(row, index, Value::I16),
+            Type::Int4 => get(row, index, Value::I32),
+            Type::Int8 => get(row, index, Value::I64),
+            Type::Oid => get(row, index, Value::U32),
+            Type::Bytea => get(row, index, Value::VecU8),
+            _ => {
+                let raw: RawValue = match row.get_opt(index){
+                    Ok(raw) => raw,
+                    Err(_) => return Ok(Value::Null),
+                };
+                let raw = &raw.0;
+                match *dtype{
+                    Type::Numeric => try!(numeric_value(raw)),
+                    Type::Json => Value::Json(try!(parse_json(&String::from_utf8_lossy(raw)))),
+                    // jsonb is prefixed with the version of its format
+                    Type::Jsonb if !raw.is_empty() => Value::Json(try!(parse_json(&String::from_utf8_lossy(&raw[1..])))),
+                    Type::Interval => Value::String(try!(decode_interval(raw))),
+                    Type::Timetz => Value::NaiveTime(try!(decode_timetz(raw))),
+                    // in the smallest unit of the currency, ie: cents
+                    Type::Money => Value::I64(try!(read_i64(&mut &raw[..], "money"))),
+                    Type::Inet | Type::Cidr => Value::String(try!(decode_inet(raw))),
+                    Type::Tsvector => Value::String(try!(decode_tsvector(raw))),
+                    Type::Other(ref other) if other.name() == "hstore" => Value::Object(try!(decode_hstore(raw))),
+                    _ if is_array(dtype) => Value::Array(try!(decode_array(dtype, raw))),
+                    // ie: enum, citext, domain types which are sent as text
+                    _ => Value::String(String::from_utf8_lossy(raw).into_owned()),
                 }
             },
-            _ => panic!("Type {:?} is not covered!", dtype)
-        }
+        };
+        Ok(value)
     }
     
 
     ///
     /// http://stackoverflow.com/questions/109325/postgresql-describe-table
     ///
-    fn get_table_columns(&self, schema:&str, table:&str)->Vec<Column>{
+    fn get_table_columns(&self, schema:&str, table:&str)->Result<Vec<Column>, DbError>{
         let sql = "
             SELECT
                 pg_attribute.attnum AS number,
@@ -251,24 +715,26 @@ impl Postgres{
                 AND pg_attribute.attnum > 0
                 ORDER BY number
             ";
-        let conn = self.get_connection();
-        let stmt = conn.prepare(&sql).unwrap();
+        let conn = try!(self.get_connection());
+        let stmt = try!(conn.prepare(&sql));
         let mut columns = Vec::new();
-        for row in stmt.query(&[&schema, &table]).unwrap() {
+        for row in try!(stmt.query(&[&schema, &table])) {
             let name:String = row.get("name");
             let not_null:bool = row.get("notnull");
             let db_data_type:String = row.get("data_type");
+            let (precision, scale) = Column::precision_and_scale(&db_data_type);
             //TODO: temporarily regex the data type to extract the size as well
-            let re = match Regex::new("(.+)\\((.+)\\)") {
+            let re = match Regex::new("(.+)\\((.+)\\)(\\[\\])?") {
                  Ok(re) => re,
-                 Err(err) => panic!("{}", err),
+                 Err(err) => return Err(DbError::new(&format!("{}", err))),
             };
 
             let db_data_type = if re.is_match(&db_data_type){
                 let cap = re.captures(&db_data_type).unwrap();
                 let data_type = cap.at(1).unwrap().to_string();
-                let size = cap.at(2).unwrap().to_string();//TODO::can be use in the later future
-                data_type
+                // keep the brackets of the arrays, ie: character varying(255)[]
+                let array = cap.at(3).unwrap_or("");
+                format!("{}{}", data_type, array)
             }else{
                 db_data_type
             };
@@ -312,7 +778,7 @@ impl Postgres{
                             }else{
                                 None
                             };
-            let (_, data_type) = self.dbtype_to_rust_type(&db_data_type);
+            let (_, data_type) = try!(self.dbtype_to_rust_type(&db_data_type));
             let column = Column{
                     name:name,
                     data_type:data_type,
@@ -324,15 +790,17 @@ impl Postgres{
                     not_null:not_null,
                     foreign:foreign,
                     is_inherited:false,//will be corrected later in the get_meta_data
+                    precision:precision,
+                    scale:scale,
                 };
             columns.push(column);
         }
         //unify due to the fact that postgresql return a separate row for
         // both primary and foreign columns
-        self.unify_primary_and_foreign_column(&columns)
+        Ok(self.unify_primary_and_foreign_column(&columns))
     }
     
-    fn get_table_comment(&self, schema:&str, table:&str)->Option<String>{
+    fn get_table_comment(&self, schema:&str, table:&str)->Result<Option<String>, DbError>{
         let sql ="
                 SELECT
                     pg_class.relname AS table,
@@ -347,16 +815,16 @@ impl Postgres{
                     AND nspname = $1
                     AND relname = $2
                 ";
-        let conn = self.get_connection();
-        let stmt = conn.prepare(&sql).unwrap();
-        for row in stmt.query(&[&schema, &table]).unwrap() {
+        let conn = try!(self.get_connection());
+        let stmt = try!(conn.prepare(&sql));
+        for row in try!(stmt.query(&[&schema, &table])) {
             let comment:Option<String> = match row.get_opt("comment"){
                     Ok(x) => Some(x),
                     Err(_) => None
                 };
-            return comment;
+            return Ok(comment);
         }
-        None
+        Ok(None)
     }
 
     /// column that is both primary and foreign should be unified
@@ -394,26 +862,26 @@ impl Postgres{
 
 impl Database for Postgres{
     
-    fn version(&self)->String{
+    fn version(&self)->Result<String, DbError>{
         let sql = "SHOW server_version";
-        let dao = self.execute_sql_with_one_return(sql, &vec![]);
-        match dao{
-            Ok(dao) => {
-                dao.get("server_version")
-            },
-            Err(_) => panic!("unable to get database version")
-        }
-        
+        let dao = try!(self.execute_sql_with_one_return(sql, &vec![]));
+        dao.try_get("server_version")
+    }
+    fn begin(&self)->Result<(), DbError>{
+        self.transaction_depth.begin(|sql| self.execute_sql(sql, &vec![]).map(|_| ()))
+    }
+    fn commit(&self)->Result<(), DbError>{
+        self.transaction_depth.commit(|sql| self.execute_sql(sql, &vec![]).map(|_| ()))
     }
-    fn begin(&self){}
-    fn commit(&self){}
-    fn rollback(&self){}
-    fn is_transacted(&self)->bool{false}
+    fn rollback(&self)->Result<(), DbError>{
+        self.transaction_depth.rollback(|sql| self.execute_sql(sql, &vec![]).map(|_| ()))
+    }
+    fn is_transacted(&self)->bool{self.transaction_depth.get() > 0}
     fn is_closed(&self)->bool{false}
     fn is_connected(&self)->bool{false}
-    fn close(&self){}
+    fn close(&self)->Result<(), DbError>{Ok(())}
     fn is_valid(&self)->bool{false}
-    fn reset(&self){}
+    fn reset(&self)->Result<(), DbError>{Ok(())}
     
     /// return this list of options, supported features in the database
     /// TODO: make this features version specific
@@ -431,24 +899,34 @@ impl Database for Postgres{
             SqlOption::SupportsInheritance,
             SqlOption::UsesSchema,
             SqlOption::ReturnMetaColumns,// whether to use the column names returned in a statement
+            SqlOption::SupportsDistinctOn,
+            SqlOption::SupportsOnConflict,
+            SqlOption::SupportsArrays,
+            SqlOption::SupportsJsonOperators,
+            SqlOption::SupportsILike,
+            SqlOption::SupportsRegex,
+            SqlOption::SupportsDistinctFrom,
+            SqlOption::SupportsNullsOrder,
         ]
     }
+
+    /// the bind message of postgresql protocol uses 16 bit for the number of parameters
+    fn max_parameters(&self)->usize{
+        65535
+    }
     
-    
-    fn update(&self, query:&Query)->Dao{panic!("not yet")}
-    fn delete(&self, query:&Query)->Result<usize, String>{panic!("not yet");}
+
 
     fn execute_sql_with_return(&self, sql:&str, params:&Vec<Value>)->Result<Vec<Dao>, DbError>{
         println!("SQL: \n{}", sql);
         println!("param: {:?}", params);
-        let conn = self.get_connection();
-        let stmt = match conn.prepare(sql){
-            Ok(stmt) => stmt,
-            Err(e) => panic!("Something is wrong, when preparing statements: {:?}",e),
-        };
+        let conn = try!(self.get_connection());
+        let stmt = try!(conn.prepare(sql));
         let mut daos = vec![];
-        let param = self.from_rust_type_tosql(params);
-        match stmt.query(&param){
+        let values = try!(self.from_rust_type_tosql(params));
+        let params = try!(Postgres::as_params(&values));
+        let params:Vec<&ToSql> = params.iter().map(|p| &**p).collect();
+        match stmt.query(&params){
             Ok(rows) =>{
                 for row in rows {
                     let columns = row.columns();
@@ -457,7 +935,7 @@ impl Database for Postgres{
                     for c in columns{
                         let column_name = c.name();
                         let dtype = c.type_();
-                        let rtype = self.from_sql_to_rust_type(&dtype, &row, index);
+                        let rtype = try!(self.from_sql_to_rust_type(&dtype, &row, index));
                         dao.set_value(column_name, rtype);
                         index += 1;
                     }
@@ -465,9 +943,7 @@ impl Database for Postgres{
                 }
                 Ok(daos)
             },
-            Err(e) => {
-                panic!("Something is wrong {:?}", e);
-            }
+            Err(e) => Err(DbError::from(e)),
         }
         
     }
@@ -478,36 +954,49 @@ impl Database for Postgres{
     fn execute_sql(&self, sql:&str, params:&Vec<Value>)->Result<usize, DbError>{
         println!("SQL: \n{}", sql);
         println!("param: {:?}", params);
-        let to_sql_types = self.from_rust_type_tosql(params);
-        let conn = self.get_connection();
-        let result = conn.execute(sql, &to_sql_types);
-        match result{
-            Ok(result) => { Ok(result as usize)},
-            Err(e) => {
-                Err(DbError::new("Something is wrong")) 
-            }
-        }
+        let values = try!(self.from_rust_type_tosql(params));
+        let conn = try!(self.get_connection());
+        let params = try!(Postgres::as_params(&values));
+        let params:Vec<&ToSql> = params.iter().map(|p| &**p).collect();
+        let result = try!(conn.execute(sql, &params));
+        Ok(result as usize)
     }
 
 }
 
 impl DatabaseDDL for Postgres{
 
-    fn create_schema(&self, schema:&str){}
-    fn drop_schema(&self, schema:&str){}
-    fn create_table(&self, model:&Table){}
-    fn build_create_table(&self, table:&Table)->SqlFrag{panic!("not yet")}
-    fn rename_table(&self, table:&Table, new_tablename:String){}
-    fn drop_table(&self, table:&Table){}
-    fn set_foreign_constraint(&self, model:&Table){}
-    fn set_primary_constraint(&self, model:&Table){}
+    fn create_schema(&self, schema:&str)->Result<(), DbError>{
+        Err(DbError::new("Creating schema is not yet supported in postgresql"))
+    }
+    fn drop_schema(&self, schema:&str)->Result<(), DbError>{
+        Err(DbError::new("Dropping schema is not yet supported in postgresql"))
+    }
+    fn create_table(&self, model:&Table)->Result<(), DbError>{
+        Err(DbError::new("Creating table is not yet supported in postgresql"))
+    }
+    fn build_create_table(&self, table:&Table)->Result<SqlFrag, DbError>{
+        Err(DbError::new("Creating table is not yet supported in postgresql"))
+    }
+    fn rename_table(&self, table:&Table, new_tablename:String)->Result<(), DbError>{
+        Err(DbError::new("Renaming table is not yet supported in postgresql"))
+    }
+    fn drop_table(&self, table:&Table)->Result<(), DbError>{
+        Err(DbError::new("Dropping table is not yet supported in postgresql"))
+    }
+    fn set_foreign_constraint(&self, model:&Table)->Result<(), DbError>{
+        Err(DbError::new("Setting foreign constraint is not yet supported in postgresql"))
+    }
+    fn set_primary_constraint(&self, model:&Table)->Result<(), DbError>{
+        Err(DbError::new("Setting primary constraint is not yet supported in postgresql"))
+    }
 
 }
 
 /// this can be condensed with using just extracting the table definition
 impl DatabaseDev for Postgres{
 
-    fn get_parent_table(&self, schema:&str, table:&str)->Option<String>{
+    fn get_parent_table(&self, schema:&str, table:&str)->Result<Option<String>, DbError>{
         let sql ="
             SELECT
                 relname as table,
@@ -521,19 +1010,19 @@ impl DatabaseDev for Postgres{
              WHERE pg_namespace.nspname = $1
                  AND relname = $2
                 ";
-        let conn = self.get_connection();
-        let stmt = conn.prepare(&sql).unwrap();
-        for row in stmt.query(&[&schema, &table]).unwrap() {
+        let conn = try!(self.get_connection());
+        let stmt = try!(conn.prepare(&sql));
+        for row in try!(stmt.query(&[&schema, &table])) {
             let parent_table:Option<String> = match row.get_opt("parent_table"){
                     Ok(x) => Some(x),
                     Err(_) => None
                 };
-            return parent_table;
+            return Ok(parent_table);
         }
-        None
+        Ok(None)
     }
 
-    fn get_table_sub_class(&self, schema:&str, table:&str)->Vec<String>{
+    fn get_table_sub_class(&self, schema:&str, table:&str)->Result<Vec<String>, DbError>{
         let sql ="
             SELECT
                 relname AS base_table,
@@ -547,30 +1036,30 @@ impl DatabaseDev for Postgres{
              AND relname = $2
              ORDER BY relname
             ";
-        let conn = self.get_connection();
-        let stmt = conn.prepare(&sql).unwrap();
+        let conn = try!(self.get_connection());
+        let stmt = try!(conn.prepare(&sql));
         let mut sub_classes:Vec<String> = vec![];
-        for row in stmt.query(&[&schema, &table]).unwrap() {
+        for row in try!(stmt.query(&[&schema, &table])) {
             match row.get_opt("sub_class"){
                     Ok(x) => sub_classes.push(x),
                     Err(_) => (),
                 };
         }
-        sub_classes
+        Ok(sub_classes)
     }
 
 
 
-    fn get_table_metadata(&self, schema:&str, table:&str, is_view: bool)->Table{
+    fn get_table_metadata(&self, schema:&str, table:&str, is_view: bool)->Result<Table, DbError>{
 
-        let mut columns = self.get_table_columns(schema, table);
-        let comment = self.get_table_comment(schema, table);
-        let parent = self.get_parent_table(schema, table);
-        let subclass = self.get_table_sub_class(schema, table);
+        let mut columns = try!(self.get_table_columns(schema, table));
+        let comment = try!(self.get_table_comment(schema, table));
+        let parent = try!(self.get_parent_table(schema, table));
+        let subclass = try!(self.get_table_sub_class(schema, table));
 
         //mutate columns to mark those which are inherited
         if parent.is_some(){
-            let inherited_columns = self.get_inherited_columns(schema, table);
+            let inherited_columns = try!(self.get_inherited_columns(schema, table));
             for i in inherited_columns{
                 for c in &mut columns{
                     if i == c.name{
@@ -580,7 +1069,7 @@ impl DatabaseDev for Postgres{
             }
         }
 
-        Table{
+        Ok(Table{
             schema:schema.to_string(),
             name:table.to_string(),
             parent_table:parent,
@@ -588,10 +1077,10 @@ impl DatabaseDev for Postgres{
             comment:comment,
             columns:columns,
             is_view: is_view
-        }
+        })
     }
 
-    fn get_all_tables(&self)->Vec<(String, String, bool)>{
+    fn get_all_tables(&self)->Result<Vec<(String, String, bool)>, DbError>{
         let sql ="
                 SELECT
                     pg_class.relname AS table,
@@ -610,21 +1099,21 @@ impl DatabaseDev for Postgres{
                 ORDER BY relname, nspname
 
                 ";
-        let conn = self.get_connection();
-        let stmt = conn.prepare(&sql).unwrap();
+        let conn = try!(self.get_connection());
+        let stmt = try!(conn.prepare(&sql));
         let mut tables:Vec<(String, String, bool)> = Vec::new();
-        for row in stmt.query(&[]).unwrap() {
+        for row in try!(stmt.query(&[])) {
             let table:String = row.get("table");
             let schema:String = row.get("schema");
             let is_view:bool = row.get("is_view");
             tables.push((schema, table, is_view));
         }
-        tables
+        Ok(tables)
     }
 
 
 
-    fn get_inherited_columns(&self, schema:&str, table:&str)->Vec<String>{
+    fn get_inherited_columns(&self, schema:&str, table:&str)->Result<Vec<String>, DbError>{
         let sql = "
                 SELECT nmsp_parent.nspname    AS parent_schema,
                     parent.relname         AS parent_table,
@@ -647,20 +1136,24 @@ impl DatabaseDev for Postgres{
                     AND child.relname = $2
                     ORDER BY column_parent.attname
                 ";
-        let conn = self.get_connection();
-        let stmt = conn.prepare(&sql).unwrap();
+        let conn = try!(self.get_connection());
+        let stmt = try!(conn.prepare(&sql));
         let mut inherited_columns = Vec::new();
-        for row in stmt.query(&[&schema, &table]).unwrap() {
+        for row in try!(stmt.query(&[&schema, &table])) {
             let column:String = row.get("column_parent_name");
             inherited_columns.push(column);
         }
-        inherited_columns
+        Ok(inherited_columns)
     }
 
 
     /// get the rust data type names from database data type names
     /// will be used in source code generation
-    fn dbtype_to_rust_type(&self, db_type: &str)->(Vec<String>, String){
+    fn dbtype_to_rust_type(&self, db_type: &str)->Result<(Vec<String>, String), DbError>{
+        if db_type.ends_with("[]"){
+            let (imports, rust_type) = try!(self.dbtype_to_rust_type(&db_type[..db_type.len() - 2]));
+            return Ok((imports, format!("Vec<{}>", rust_type)));
+        }
         let db_type = match db_type{
             "boolean" => {
                 (vec![], "bool".to_string() )
@@ -683,20 +1176,20 @@ impl DatabaseDev for Postgres{
             "real" => {
                 (vec![], "f32".to_string() )
             },
-            "double precision" | "numeric" => {
+            "double precision" => {
                 (vec![], "f64".to_string() )
             },
+            "numeric" | "decimal" => {
+                (vec!["rustorm::dao::Decimal".to_string()], "Decimal".to_string() )
+            },
             "name" | "character" | "character varying" | "text" | "citext" | "bpchar" =>{
                 ( vec![], "String".to_string() )
             },
             "bytea" =>{
                 ( vec![], "Vec<u8>".to_string() )
             },
-            //"json" | "jsonb" => {
-            //    ((Some(vec!["rustc_serialize::json::Json".to_string()]), "Json".to_string()))
-           // },
-            "json" | "jsonb" => {//FIXME :String for now, since Json itself is not encodable
-                ((vec![], "String".to_string()))
+            "json" | "jsonb" => {
+                (vec!["rustc_serialize::json::Json".to_string()], "Json".to_string() )
             },
             "uuid" => {
                 (vec!["uuid::Uuid".to_string()], "Uuid".to_string() )
@@ -725,24 +1218,28 @@ impl DatabaseDev for Postgres{
                 (vec!["std::collections::HashMap".to_string()], "HashMap<String, Option<String>>".to_string())
             },
             "interval" => {
-                (vec![], "u32".to_string() )
-            },
-            "inet[]" => {
                 (vec![], "String".to_string() )
             },
+            "money" => {
+                (vec![], "i64".to_string() )
+            },
             "tsvector" | "inet" => {
                 (vec![], "String".to_string() )
             },//or everything else should be string
-            _ => panic!("Unable to get the equivalent data type for {}", db_type),
+            _ => return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to get the equivalent data type for {}", db_type))),
         };
-        db_type
+        Ok(db_type)
     }
 
     ///
     /// convert rust data type names to database data type names
     /// will be used in generating SQL for table creation
     /// FIXME, need to restore the exact data type as before
-    fn rust_type_to_dbtype(&self, rust_type: &str)->String{
+    fn rust_type_to_dbtype(&self, rust_type: &str)->Result<String, DbError>{
+        if rust_type.starts_with("Vec<") && rust_type.ends_with(">") && rust_type != "Vec<u8>"{
+            let element_type = try!(self.rust_type_to_dbtype(&rust_type[4..rust_type.len() - 1]));
+            return Ok(format!("{}[]", element_type));
+        }
 
         let rust_type = match rust_type{
             "bool" => {
@@ -767,6 +1264,9 @@ impl DatabaseDev for Postgres{
                 "real".to_string()
             },
             "f64" => {
+                "double precision".to_string()
+            },
+            "Decimal" => {
                 "numeric".to_string()
             },
             "String" =>{
@@ -796,10 +1296,48 @@ impl DatabaseDev for Postgres{
             "HashMap<String, Option<String>>" => {
                 "hstore".to_string()
             },
-            _ => panic!("Unable to get the equivalent database data type for {}", rust_type),
+            _ => return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to get the equivalent database data type for {}", rust_type))),
         };
-        rust_type
+        Ok(rust_type)
+
+    }
 
+}
+
+#[test]
+fn test_decode_binary(){
+    // 12345.6789 as base 10000 digits: 1, 2345, 6789 with weight 1, scale 4
+    let numeric = [0,3, 0,1, 0,0, 0,4, 0,1, 0x09,0x29, 0x1a,0x85];
+    assert_eq!(decode_numeric(&numeric).unwrap(), "12345.6789");
+    // -0.05 with weight -1, scale 2
+    let numeric = [0,1, 0xff,0xff, 0x40,0, 0,2, 0x01,0xf4];
+    assert_eq!(decode_numeric(&numeric).unwrap(), "-0.05");
+    // 1 year 2 mons 3 days 04:05:06
+    let interval = [0,0,0,3,0x6c,0x8b,0xc0,0x80, 0,0,0,3, 0,0,0,14];
+    assert_eq!(decode_interval(&interval).unwrap(), "1 year 2 mons 3 days 04:05:06");
+    let hstore = [0,0,0,1, 0,0,0,1, b'a', 0xff,0xff,0xff,0xff];
+    assert_eq!(decode_hstore(&hstore).unwrap().get("a"), Some(&Value::Null));
+    assert!(decode_numeric(&[0,1]).is_err());
+    // {1,NULL} of integer[]
+    let array = [0,0,0,1, 0,0,0,1, 0,0,0,23, 0,0,0,2, 0,0,0,1, 0,0,0,4, 0,0,0,1, 0xff,0xff,0xff,0xff];
+    assert_eq!(decode_array(&Type::Int4Array, &array).unwrap(), vec![Value::I32(1), Value::Null]);
+    for text in &["12345.6789", "-0.05", "0", "100000", "0.00010", "1.5"]{
+        let decimal = Decimal::from_str(text).unwrap();
+        assert_eq!(decode_numeric(&encode_numeric(&decimal)).unwrap(), *text);
     }
+}
 
+#[test]
+fn test_bind_unsigned_and_null(){
+    let db = Postgres::new();
+    let values = db.from_rust_type_tosql(&vec![Value::U8(255), Value::U16(65535), Value::U32(4294967295), Value::Null]).unwrap();
+    assert_eq!(values, vec![Value::I16(255), Value::I32(65535), Value::I64(4294967295), Value::Null]);
+    assert_eq!(Postgres::as_params(&values).unwrap().len(), 4);
+    assert!(Postgres::as_params(&vec![Value::U8(255)]).is_err());
+    let mut info = BTreeMap::new();
+    info.insert("color".to_string(), Value::String("red".to_string()));
+    let values = db.from_rust_type_tosql(&vec![Value::Object(info)]).unwrap();
+    assert_eq!(values, vec![Value::Json(Json::from_str("{\"color\":\"red\"}").unwrap())]);
+    assert_eq!(db.from_rust_type_tosql(&vec![Value::Array(vec![Value::U8(1)])]).unwrap(), vec![Value::Array(vec![Value::I16(1)])]);
+    assert!(db.from_rust_type_tosql(&vec![Value::U64(u64::max_value())]).is_err());
 }
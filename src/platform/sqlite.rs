@@ -1,72 +1,301 @@
-use query::Query;
-use dao::Dao;
+use query::{Query, Operand};
+use dao::{Dao, DaoResult};
 
 use dao::Value;
+use dao::FromValue;
 use database::{Database,DatabaseDev};
 use writer::SqlFrag;
 use database::SqlOption;
+use database::TransactionDepth;
 use rusqlite::SqliteConnection;
-use rusqlite::types::ToSql;
+use rusqlite::types::{ToSql, Null};
+use rusqlite::types::Value as SqliteValue;
 use rusqlite::SqliteRow;
+use rusqlite::SqliteError;
 use table::{Table, Column, Foreign};
 use database::DatabaseDDL;
-use database::DbError;
+use database::{DbError, DbErrorKind};
 use r2d2::PooledConnection;
 use r2d2_sqlite::SqliteConnectionManager;
 use regex::Regex;
+use rustc_serialize::json::ToJson;
 use std::collections::BTreeMap;
+use std::cell::RefCell;
 
 pub struct Sqlite {
     pool: Option<PooledConnection<SqliteConnectionManager>>,
+    /// the number of nested transactions in progress on this connection
+    transaction_depth: TransactionDepth,
+    /// the declared types of the columns of each table, used to type the values
+    declared_types: RefCell<BTreeMap<String, BTreeMap<String, String>>>,
 }
 
 
+/// map the error from the driver to the kind of database error,
+/// the original error is kept as the cause
+impl From<SqliteError> for DbError{
+
+    fn from(e: SqliteError)->Self{
+        let kind = Sqlite::error_kind(e.code, &e.message);
+        DbError::from_driver(kind, e)
+    }
+}
+
 impl Sqlite{
     
     pub fn new()->Self{
-        Sqlite{pool: None}
+        Sqlite{pool: None, transaction_depth: TransactionDepth::new(), declared_types: RefCell::new(BTreeMap::new())}
     }
     
+    /// sqlite reports all constraint violations as SQLITE_CONSTRAINT,
+    /// the kind of violation is in the message ie: `UNIQUE constraint failed: product.name`,
+    /// older versions use `column name is not unique`, `product.name may not be NULL`
+    fn error_kind(code: i32, message: &str)->DbErrorKind{
+        let subject = message.split(": ").nth(1).map(|s| s.to_string());
+        match code{
+            19 /* SQLITE_CONSTRAINT */ => {
+                if message.starts_with("UNIQUE") || message.starts_with("PRIMARY KEY") || message.ends_with("not unique"){
+                    DbErrorKind::UniqueViolation{constraint: subject}
+                }else if message.starts_with("NOT NULL") || message.ends_with("may not be NULL"){
+                    DbErrorKind::NotNullViolation{column: subject}
+                }else if message.starts_with("FOREIGN KEY"){
+                    DbErrorKind::ForeignKeyViolation{constraint: subject}
+                }else{
+                    DbErrorKind::Other
+                }
+            },
+            14 /* SQLITE_CANTOPEN */ | 26 /* SQLITE_NOTADB */ => DbErrorKind::Connection,
+            20 /* SQLITE_MISMATCH */ => DbErrorKind::TypeConversion,
+            1 /* SQLITE_ERROR */ if message.contains("syntax error") => DbErrorKind::Syntax,
+            _ => DbErrorKind::Other,
+        }
+    }
+
     pub fn with_pooled_connection(pool: PooledConnection<SqliteConnectionManager>)->Self{
-       Sqlite{pool: Some(pool)}
+       Sqlite{pool: Some(pool), transaction_depth: TransactionDepth::new(), declared_types: RefCell::new(BTreeMap::new())}
     }
     
-    fn from_rust_type_tosql<'a>(&self, types: &'a Vec<Value>)->Vec<&'a ToSql>{
-        let mut params:Vec<&ToSql> = vec![];
+    /// convert the values into the sqlite storage classes: INTEGER, REAL, TEXT, BLOB and NULL,
+    /// bool is stored as 0 and 1, Uuid and the dates as TEXT, in the format sqlite date functions understands
+    fn from_rust_type_tosql(&self, types: &Vec<Value>)->Result<Vec<Value>, DbError>{
+        let mut params = vec![];
         for t in types{
-            match t {
-                &Value::String(ref x) => {
-                    params.push(x);
+            let value = match *t {
+                Value::Bool(x) => Value::I64(if x { 1 } else { 0 }),
+                Value::I8(x) => Value::I64(x as i64),
+                Value::I16(x) => Value::I64(x as i64),
+                Value::I32(x) => Value::I64(x as i64),
+                Value::I64(x) => Value::I64(x),
+                Value::U8(x) => Value::I64(x as i64),
+                Value::U16(x) => Value::I64(x as i64),
+                Value::U32(x) => Value::I64(x as i64),
+                Value::U64(x) => {
+                    if x > i64::max_value() as u64 {
+                        return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to bind {:?}, it does not fit in sqlite INTEGER", t)));
+                    }
+                    Value::I64(x as i64)
                 },
-                _ => panic!("not yet here {:?}", t),
+                Value::F32(x) => Value::F64(x as f64),
+                Value::F64(x) => Value::F64(x),
+                Value::Decimal(ref x) => Value::String(x.to_string()),
+                Value::String(ref x) => Value::String(x.to_string()),
+                Value::VecU8(ref x) => Value::VecU8(x.clone()),
+                Value::Object(_) => Value::String(t.to_json().to_string()),
+                Value::Array(_) => {
+                    return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to bind {:?}, sqlite does not support arrays", t)));
+                },
+                Value::Json(ref x) => Value::String(x.to_string()),
+                Value::Uuid(ref x) => Value::String(x.to_hyphenated_string()),
+                Value::DateTime(ref x) => Value::String(x.to_rfc3339()),
+                Value::NaiveDate(ref x) => Value::String(x.format("%Y-%m-%d").to_string()),
+                Value::NaiveTime(ref x) => Value::String(x.format("%H:%M:%S%.f").to_string()),
+                Value::NaiveDateTime(ref x) => Value::String(x.format("%Y-%m-%d %H:%M:%S%.f").to_string()),
+                Value::Null => Value::Null,
+            };
+            params.push(value);
+        }
+        Ok(params)
+    }
+
+    /// the values converted from from_rust_type_tosql as parameters
+    fn as_params<'a>(values: &'a Vec<Value>)->Vec<&'a ToSql>{
+        static NULL: Null = Null;
+        let mut params:Vec<&ToSql> = vec![];
+        for v in values{
+            match *v {
+                Value::I64(ref x) => params.push(x),
+                Value::F64(ref x) => params.push(x),
+                Value::String(ref x) => params.push(x),
+                Value::VecU8(ref x) => params.push(x),
+                _ => params.push(&NULL),
             };
         }
         params
     }
     
-    pub fn get_connection(&self)->&SqliteConnection{
-       if self.pool.is_some(){
-            &self.pool.as_ref().unwrap()
-        }
-        else{
-            panic!("No connection for this database")
+    pub fn get_connection(&self)->Result<&SqliteConnection, DbError>{
+        match self.pool{
+            Some(ref pool) => Ok(&**pool),
+            None => Err(DbError::with_kind(DbErrorKind::Connection, "No connection for this database")),
         }
     }
     
-        /// convert a record of a row into rust type
-    fn from_sql_to_rust_type(&self, row: &SqliteRow, index:usize)->Value{
-        let value = row.get_opt(index as i32);
-         match value{
-            Ok(value) => Value::String(value),
-            Err(_) => Value::Null,
+    /// convert a record of a row into rust type, based on the storage class of the value:
+    /// INTEGER, REAL, TEXT, BLOB or NULL
+    fn from_sql_to_rust_type(&self, row: &SqliteRow, index:usize)->Result<Value, DbError>{
+        let value = try!(row.get_opt::<SqliteValue>(index as i32));
+        let value = match value{
+            SqliteValue::Integer(x) => Value::I64(x),
+            SqliteValue::Real(x) => Value::F64(x),
+            SqliteValue::Text(x) => Value::String(x),
+            SqliteValue::Blob(x) => Value::VecU8(x),
+            SqliteValue::Null => Value::Null,
+        };
+        Ok(value)
+    }
+
+    /// recover the types sqlite does not have a storage class for,
+    /// using the declared type of the column ie: uuid, boolean, timestamp, date, time.
+    /// the value is kept as is when it is not in the format of the declared type
+    fn from_declared_type(declared: &str, value: Value)->Value{
+        fn convert<T: FromValue>(value: &Value, variant: fn(T)->Value)->Option<Value>{
+            FromValue::from_type(value.clone()).ok().map(variant)
+        }
+        if value == Value::Null{
+            return value;
+        }
+        let declared = declared.to_uppercase();
+        let converted = if declared.contains("UUID"){
+            convert(&value, Value::Uuid)
+        }else if declared.starts_with("BOOL"){
+            convert(&value, Value::Bool)
+        }else if declared.contains("TIMESTAMP") || declared.contains("DATETIME"){
+            if declared.contains("TIME ZONE") || declared.contains("TZ"){
+                convert(&value, Value::DateTime)
+            }else{
+                convert(&value, Value::NaiveDateTime)
+            }
+        }else if declared.starts_with("JSON"){
+            convert(&value, Value::Json)
+        }else if declared.starts_with("NUMERIC") || declared.starts_with("DECIMAL"){
+            convert(&value, Value::Decimal)
+        }else if declared.starts_with("DATE"){
+            convert(&value, Value::NaiveDate)
+        }else if declared.starts_with("TIME"){
+            convert(&value, Value::NaiveTime)
+        }else{
+            None
+        };
+        converted.unwrap_or(value)
+    }
+
+    /// the declared type of each column of the table, from `PRAGMA table_info`,
+    /// looked up once for each table on this connection until a DDL statement is executed
+    fn get_declared_types(&self, table:&str)->Result<BTreeMap<String, String>, DbError>{
+        if let Some(declared_types) = self.declared_types.borrow().get(table){
+            return Ok(declared_types.clone());
         }
+        let sql = format!("PRAGMA table_info(\"{}\");", table.replace("\"", "\"\""));
+        let result = try!(self.execute_sql_with_return(&sql, &vec![]));
+        let mut declared_types = BTreeMap::new();
+        for r in result{
+            let name: String = try!(r.try_get("name"));
+            let data_type: String = try!(r.try_get("type"));
+            declared_types.insert(name, data_type);
+        }
+        self.declared_types.borrow_mut().insert(table.to_string(), declared_types.clone());
+        Ok(declared_types)
+    }
+
+    /// the declared types of the columns in the result which are columns of the tables in the query,
+    /// expressions and the columns that can come from more than one table are not typed
+    fn get_result_declared_types(&self, query:&Query)->Result<BTreeMap<String, String>, DbError>{
+        // the names the table can be referred to, and the declared types of its columns
+        let mut tables = vec![];
+        if let Some(ref from) = query.from{
+            if let Operand::TableName(ref table_name) = from.operand{
+                let mut names = vec![table_name.name.to_string()];
+                if let Some(ref alias) = from.name{
+                    names.push(alias.to_string());
+                }
+                tables.push((names, try!(self.get_declared_types(&table_name.name))));
+            }
+        }
+        for join in &query.joins{
+            let names = vec![join.table_name.name.to_string()];
+            tables.push((names, try!(self.get_declared_types(&join.table_name.name))));
+        }
+        let mut result_types = BTreeMap::new();
+        for field in &query.enumerated_fields{
+            let column = match field.operand{
+                Operand::ColumnName(ref column) => column,
+                _ => {
+                    if let Some(ref name) = field.name{
+                        add_result_type(&mut result_types, name, None);
+                    }
+                    continue;
+                },
+            };
+            let sources: Vec<&BTreeMap<String, String>> = tables.iter()
+                .filter(|&&(ref names, _)| match column.table{
+                    Some(ref table) => names.contains(table),
+                    None => true,
+                })
+                .map(|&(_, ref types)| types)
+                .collect();
+            if column.column == "*"{
+                for types in sources{
+                    for (name, declared) in types{
+                        add_result_type(&mut result_types, name, Some(declared.to_string()));
+                    }
+                }
+            }else{
+                let declared: Vec<&String> = sources.iter()
+                    .filter_map(|types| types.get(&column.column))
+                    .collect();
+                let declared = if declared.len() == 1 { Some(declared[0].to_string()) } else { None };
+                let name = match field.name{
+                    Some(ref name) => name,
+                    None => &column.column,
+                };
+                add_result_type(&mut result_types, name, declared);
+            }
+        }
+        let mut declared_types = BTreeMap::new();
+        for (name, declared) in result_types{
+            if let Some(declared) = declared{
+                declared_types.insert(name, declared);
+            }
+        }
+        Ok(declared_types)
+    }
+
+    /// convert the values of the records using the declared types of the columns
+    /// of the tables used in the query
+    fn apply_declared_types(&self, query:&Query, daos:&mut Vec<Dao>)->Result<(), DbError>{
+        let declared_types = try!(self.get_result_declared_types(query));
+        for dao in daos{
+            for (column, value) in dao.values.iter_mut(){
+                if let Some(declared) = declared_types.get(column){
+                    *value = Sqlite::from_declared_type(declared, value.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// execute the query, then type the values using the declared types of the columns
+    fn execute_query_typed(&self, query:&Query, sql_frag:&SqlFrag)->Result<Vec<Dao>, DbError>{
+        let mut daos = try!(self.execute_sql_with_return(&sql_frag.sql, &sql_frag.params));
+        try!(self.apply_declared_types(query, &mut daos));
+        Ok(daos)
     }
     
     ///
     /// convert rust data type names to database data type names
     /// will be used in generating SQL for table creation
     /// FIXME, need to restore the exact data type as before
-    fn rust_type_to_dbtype(&self, rust_type: &str)->String{
+    fn rust_type_to_dbtype(&self, rust_type: &str)->Result<String, DbError>{
 
         let rust_type = match rust_type{
             "bool" => {
@@ -93,6 +322,11 @@ impl Sqlite{
             "f64" => {
                 "real".to_string()
             },
+            // a numeric column would store the text as REAL, losing the scale and precision,
+            // the decimal is bound as text and kept as is in a text column
+            "Decimal" => {
+                "text".to_string()
+            },
             "String" =>{
                 "text".to_string()
             },
@@ -103,26 +337,26 @@ impl Sqlite{
                 "text".to_string()
             },
             "Uuid" => {
-                "text".to_string()
+                "uuid".to_string()
             },
             "NaiveDateTime" => {
-                "numeric".to_string()
+                "timestamp".to_string()
             },
             "DateTime<UTC>" => {
-                "numeric".to_string()
+                "timestamp with time zone".to_string()
             },
             "NaiveDate" => {
-                "numeric".to_string()
+                "date".to_string()
             },
             "NaiveTime" => {
-                "numeric".to_string()
+                "time".to_string()
             },
             "HashMap<String, Option<String>>" => {
                 "text".to_string()
             },
-            _ => panic!("Unable to get the equivalent database data type for {}", rust_type),
+            _ => return Err(DbError::with_kind(DbErrorKind::TypeConversion, &format!("Unable to get the equivalent database data type for {}", rust_type))),
         };
-        rust_type
+        Ok(rust_type)
 
     }
     
@@ -163,7 +397,7 @@ impl Sqlite{
                     Result<(Option<String>, BTreeMap<String, Option<String>>),DbError>{
         let re = match Regex::new(r".*CREATE\s+TABLE\s+(\S+)\s*\((?s)(.*)\).*") {
             Ok(re) => re,
-            Err(err) => panic!("{}", err),
+            Err(err) => return Err(DbError::new(&format!("{}", err))),
         };
         println!("create_sql: {:?}", create_sql);
         if re.is_match(&create_sql){
@@ -173,7 +407,7 @@ impl Sqlite{
             
             let line_comma_re = match Regex::new(r"[,\n]") {
                 Ok(re) => re,
-                Err(err) => panic!("{}", err),
+                Err(err) => return Err(DbError::new(&format!("{}", err))),
             };
             println!("All columns.. {}", all_columns);
             let splinters:Vec<&str> = line_comma_re.split(all_columns).collect();
@@ -232,10 +466,12 @@ impl Sqlite{
         let sql = format!("SELECT sql FROM sqlite_master WHERE type = 'table' AND tbl_name = '{}'",table);
         let result = self.execute_sql_with_return(&sql, &vec![]);
         match result{
-            Ok(result) => {
-                assert_eq!(result.len(), 1);
+            Ok(ref result) if result.len() == 1 => {
                 let ref dao = result[0];
-                let create_sql:String = dao.get("sql");
+                let create_sql:String = match dao.try_get("sql"){
+                    Ok(create_sql) => create_sql,
+                    Err(_) => return None,
+                };
                 match Sqlite::extract_comments(&create_sql){
                     Ok((table_comment, column_comments)) => {
                         println!("table_comment: {:?}", table_comment);
@@ -246,7 +482,7 @@ impl Sqlite{
                     }
                 }
             },
-            Err(e) => None
+            _ => None
         }
     }
     /// extract the comments for each column
@@ -255,10 +491,12 @@ impl Sqlite{
         let sql = format!("SELECT sql FROM sqlite_master WHERE type = 'table' AND tbl_name = '{}'",table);
         let result = self.execute_sql_with_return(&sql, &vec![]);
         match result{
-            Ok(result) => {
-                assert_eq!(result.len(), 1);
+            Ok(ref result) if result.len() == 1 => {
                 let ref dao = result[0];
-                let create_sql:String = dao.get("sql");
+                let create_sql:String = match dao.try_get("sql"){
+                    Ok(create_sql) => create_sql,
+                    Err(_) => return BTreeMap::new(),
+                };
                 match Sqlite::extract_comments(&create_sql){
                     Ok((table_comment, column_comments)) => {
                         println!("column_comments: {:?}", column_comments);
@@ -269,7 +507,7 @@ impl Sqlite{
                     }
                 }
             },
-            Err(e) => BTreeMap::new()
+            _ => BTreeMap::new()
         }
     }
     
@@ -291,46 +529,92 @@ impl Sqlite{
     }
 }
 
+/// the declared type of a result column, a name that appears more than once in the result
+/// can have the value from any of the columns so it is not typed
+fn add_result_type(result_types: &mut BTreeMap<String, Option<String>>, name: &str, declared: Option<String>){
+    if result_types.contains_key(name){
+        result_types.insert(name.to_string(), None);
+    }else{
+        result_types.insert(name.to_string(), declared);
+    }
+}
+
+/// the statement changes the structure of the tables
+fn is_ddl(sql: &str)->bool{
+    let sql = sql.trim_left().to_uppercase();
+    sql.starts_with("CREATE") || sql.starts_with("ALTER") || sql.starts_with("DROP")
+}
+
 impl Database for Sqlite{
-    fn version(&self)->String{
+    fn version(&self)->Result<String, DbError>{
        let sql = "select sqlite_version() as version";
-       let dao = self.execute_sql_with_return(sql, &vec![]);
-       match dao{
-            Ok(dao) => {
-                if dao.len() == 1{
-                    return dao[0].get("version")
-                }
-                else{
-                    return "unknown".to_string()
-                }
-            },
-            Err(_) => panic!("unable to get database version")
-        }
+       let dao = try!(self.execute_sql_with_one_return(sql, &vec![]));
+       dao.try_get("version")
+    }
+    fn begin(&self)->Result<(), DbError>{
+        self.transaction_depth.begin(|sql| self.execute_sql(sql, &vec![]).map(|_| ()))
+    }
+    fn commit(&self)->Result<(), DbError>{
+        self.transaction_depth.commit(|sql| self.execute_sql(sql, &vec![]).map(|_| ()))
+    }
+    fn rollback(&self)->Result<(), DbError>{
+        self.transaction_depth.rollback(|sql| self.execute_sql(sql, &vec![]).map(|_| ()))
     }
-    fn begin(&self){}
-    fn commit(&self){}
-    fn rollback(&self){}
-    fn is_transacted(&self)->bool{false}
+    fn is_transacted(&self)->bool{self.transaction_depth.get() > 0}
     fn is_closed(&self)->bool{false}
     fn is_connected(&self)->bool{false}
-    fn close(&self){}
+    fn close(&self)->Result<(), DbError>{Ok(())}
     fn is_valid(&self)->bool{false}
-    fn reset(&self){}
+    fn reset(&self)->Result<(), DbError>{Ok(())}
     
     /// return this list of options, supported features in the database
     fn sql_options(&self)->Vec<SqlOption>{
         vec![
             SqlOption::UsesNumberedParam,  // uses numbered parameters
             SqlOption::SupportsCTE,
+            SqlOption::SupportsOnConflict,
+            SqlOption::SupportsJsonExtract,
+            SqlOption::UsesIsForDistinctFrom,
         ]
     }
+
+    /// SQLITE_MAX_VARIABLE_NUMBER defaults to 999
+    fn max_parameters(&self)->usize{
+        999
+    }
     
     fn insert(&self, query:&Query)->Result<Dao, DbError>{
-        let sql_frag = self.build_insert(query);
-        self.execute_sql_with_one_return(&sql_frag.sql, &sql_frag.params)
+        let sql_frag = try!(self.build_insert(query));
+        let mut daos = try!(self.execute_query_typed(query, &sql_frag));
+        match daos.len(){
+            1 => Ok(daos.remove(0)),
+            0 => Err(DbError::not_found("There is no record returned")),
+            _ => Err(DbError::new("There should be 1 and only 1 record return here")),
+        }
+    }
+
+    /// the values are typed using the declared types of the columns
+    fn execute_with_return(&self, query:&Query)->Result<DaoResult, DbError>{
+        let sql_frag = try!(self.build_query(query));
+        let daos = try!(self.execute_query_typed(query, &sql_frag));
+        Ok(DaoResult{
+            dao: daos,
+            renamed_columns: query.get_renamed_columns(),
+            total: None,
+            page: None,
+            page_size: None,
+        })
+    }
+
+    fn execute_with_one_return(&self, query:&Query)->Result<Dao, DbError>{
+        let sql_frag = try!(self.build_query(query));
+        let mut daos = try!(self.execute_query_typed(query, &sql_frag));
+        match daos.len(){
+            1 => Ok(daos.remove(0)),
+            0 => Err(DbError::not_found("There is no record returned")),
+            _ => Err(DbError::new("There should be 1 and only 1 record return here")),
+        }
     }
-    fn update(&self, query:&Query)->Dao{panic!("not yet")}
-    fn delete(&self, query:&Query)->Result<usize, String>{panic!("not yet");}
     
     /// sqlite does not return the columns mentioned in the query,
     /// you have to specify it yourself
@@ -339,85 +623,61 @@ impl Database for Sqlite{
     fn execute_sql_with_return(&self, sql:&str, params:&Vec<Value>)->Result<Vec<Dao>, DbError>{
         println!("SQL: \n{}", sql);
         println!("param: {:?}", params);
-        let conn = self.get_connection();
-        let mut stmt = conn.prepare(sql).unwrap();
+        let conn = try!(self.get_connection());
+        let mut stmt = try!(conn.prepare(sql));
         let mut daos = vec![];
-        let param = self.from_rust_type_tosql(params);
+        let values = try!(self.from_rust_type_tosql(params));
+        let param = Sqlite::as_params(&values);
         let mut columns = vec![];
         for c in stmt.column_names(){
             columns.push(c.to_string());
         }
         println!("columns : {:?}", columns);
-        match stmt.query(&param){
-            Ok(rows) => 
-            for row in rows {
-                match row{
-                    Ok(row) => {
-                        let mut index = 0;
-                        let mut dao = Dao::new();
-                        for col in &columns{
-                            let rtype = self.from_sql_to_rust_type(&row, index);
-                            println!("{:?}",rtype);
-                            dao.set_value(col, rtype);
-                            index += 1;
-                        }
-                        daos.push(dao);
-                    }
-                    Err(e) => {
-                        println!("error! {}",e) 
-                    }
-                }
-            },
-            Err(e) => println!("Something is wrong")
+        let rows = try!(stmt.query(&param));
+        for row in rows {
+            let row = try!(row);
+            let mut index = 0;
+            let mut dao = Dao::new();
+            for col in &columns{
+                let rtype = try!(self.from_sql_to_rust_type(&row, index));
+                dao.set_value(col, rtype);
+                index += 1;
+            }
+            daos.push(dao);
         }
         Ok(daos)
     }
 
     
-    fn execute_sql_with_one_return(&self, sql:&str, params:&Vec<Value>)->Result<Dao, DbError>{
-        let dao = self.execute_sql_with_return(sql, params);
-        match dao{
-            Ok(dao) => {
-                if dao.len() == 1{
-                    return Ok(dao[0].clone())
-                }
-                else{
-                    return Err(DbError::new("There should be 1 and only 1 record return here"))
-                }
-            },
-            Err(e) => Err(DbError::new("Error in the query"))
-        }
-    }
-    
     /// generic execute sql which returns not much information,
     /// returns only the number of affected records or errors
     /// can be used with DDL operations (CREATE, DELETE, ALTER, DROP)
     fn execute_sql(&self, sql:&str, params:&Vec<Value>)->Result<usize, DbError>{
         println!("SQL: \n{}", sql);
         println!("param: {:?}", params);
-        let to_sql_types = self.from_rust_type_tosql(params);
-        let conn = self.get_connection();
-        let result = conn.execute(sql, &to_sql_types);
-        match result{
-            Ok(result) => { Ok(result as usize)},
-            Err(e) => {
-                Err(DbError::new(&format!("Something is wrong, {}", e))) 
-            }
+        let values = try!(self.from_rust_type_tosql(params));
+        let to_sql_types = Sqlite::as_params(&values);
+        let conn = try!(self.get_connection());
+        let result = try!(conn.execute(sql, &to_sql_types));
+        if is_ddl(sql){
+            // the declared types of the tables may have changed
+            self.declared_types.borrow_mut().clear();
         }
+        Ok(result as usize)
     }
 
 }
 
 impl DatabaseDDL for Sqlite{
-    fn create_schema(&self, schema:&str){
-        panic!("sqlite does not support schema")
+    fn create_schema(&self, schema:&str)->Result<(), DbError>{
+        Err(DbError::new("sqlite does not support schema"))
     }
 
-    fn drop_schema(&self, schema:&str){
-        panic!("sqlite does not support schema")
+    fn drop_schema(&self, schema:&str)->Result<(), DbError>{
+        Err(DbError::new("sqlite does not support schema"))
     }
     
-    fn build_create_table(&self, table:&Table)->SqlFrag{
+    fn build_create_table(&self, table:&Table)->Result<SqlFrag, DbError>{
         
         fn build_foreign_key_stmt(table: &Table)->SqlFrag{
             let mut w = SqlFrag::new(vec![]);
@@ -447,7 +707,7 @@ impl DatabaseDDL for Sqlite{
             if do_comma {w.commasp();w.ln_tab();}else {do_comma=true;}
             w.append(&c.name);
             w.append(" ");
-            let dt = self.rust_type_to_dbtype(&c.data_type);
+            let dt = try!(self.rust_type_to_dbtype(&c.data_type));
             w.append(&dt);
             if c.is_primary {
                 w.append(" PRIMARY KEY ");
@@ -457,39 +717,39 @@ impl DatabaseDDL for Sqlite{
         w.append(&fsql.sql);
         w.ln();
         w.append(")");
-        w
+        Ok(w)
     }
-    fn create_table(&self, table:&Table){
-         let frag = self.build_create_table(table);
-         match self.execute_sql(&frag.sql, &vec![]){
-            Ok(x) => println!("created table.."),
-            Err(e) => panic!("table not created {}", e),
-         }
+    fn create_table(&self, table:&Table)->Result<(), DbError>{
+         let frag = try!(self.build_create_table(table));
+         try!(self.execute_sql(&frag.sql, &vec![]));
+         Ok(())
     }
 
-    fn rename_table(&self, table:&Table, new_tablename:String){
-        
+    fn rename_table(&self, table:&Table, new_tablename:String)->Result<(), DbError>{
+        Err(DbError::new("Renaming table is not yet supported in sqlite"))
     }
 
-    fn drop_table(&self, table:&Table){
-        panic!("not yet");
+    fn drop_table(&self, table:&Table)->Result<(), DbError>{
+        Err(DbError::new("Dropping table is not yet supported in sqlite"))
     }
 
-    fn set_foreign_constraint(&self, model:&Table){
-        panic!("not yet");
+    /// sqlite can only declare constraints when creating the table
+    fn set_foreign_constraint(&self, model:&Table)->Result<(), DbError>{
+        Err(DbError::new("sqlite does not support adding constraints to an existing table"))
     }
 
-    fn set_primary_constraint(&self, model:&Table){
-        panic!("not yet");
+    fn set_primary_constraint(&self, model:&Table)->Result<(), DbError>{
+        Err(DbError::new("sqlite does not support adding constraints to an existing table"))
     }
 }
 
 impl DatabaseDev for Sqlite{
-    fn get_table_sub_class(&self, schema:&str, table:&str)->Vec<String>{panic!("not yet")}
+    /// sqlite does not support table inheritance
+    fn get_table_sub_class(&self, schema:&str, table:&str)->Result<Vec<String>, DbError>{Ok(vec![])}
 
-    fn get_parent_table(&self, schema:&str, table:&str)->Option<String>{panic!("not yet")}
+    fn get_parent_table(&self, schema:&str, table:&str)->Result<Option<String>, DbError>{Ok(None)}
     
-    fn get_table_metadata(&self, schema:&str, table:&str, is_view: bool)->Table{
+    fn get_table_metadata(&self, schema:&str, table:&str, is_view: bool)->Result<Table, DbError>{
         println!("extracting table meta data in sqlite");
         let sql = format!("PRAGMA table_info({});", table);
         let result = self.execute_sql_with_return(&sql, &vec![]);
@@ -502,34 +762,37 @@ impl DatabaseDev for Sqlite{
                 
                 let mut columns = vec![];
                 for r in result{
-                    let column: String = r.get("name");
-                    let data_type: String = r.get("type");
-                    let default_value: String = r.get("dflt_value");
-                    let not_null: String = r.get("notnull");
-                    let pk: String = r.get("pk");
+                    let column: String = try!(r.try_get("name"));
+                    let data_type: String = try!(r.try_get("type"));
+                    let default_value: Option<String> = try!(r.try_get_opt("dflt_value"));
+                    let not_null: bool = try!(r.try_get("notnull"));
+                    let pk: i64 = try!(r.try_get("pk"));
                     println!("column: {}", column);
                     println!("data_type: {}", data_type);
                     println!("not null: {}", not_null);
                     println!("pk: {}", pk);
-                    println!("default_value: {}", default_value);
+                    println!("default_value: {:?}", default_value);
                     
                     let column_comment = self.get_column_comment(&column_comments, &column);
                     let column_foreign = self.get_column_foreign(&foreign, &column);
+                    let (precision, scale) = Column::precision_and_scale(&data_type);
                     let column = Column{
                             name: column,
                             data_type: data_type.to_string(),
                             db_data_type: data_type.to_string(),
-                            is_primary: pk != "0",
+                            is_primary: pk != 0,
                             is_unique: false,
-                            default: Some(default_value),
+                            default: default_value,
                             comment: column_comment,
-                            not_null : not_null != "0",
+                            not_null : not_null,
                             is_inherited: false,
                             foreign: column_foreign,
+                            precision: precision,
+                            scale: scale,
                         };
                     columns.push(column);
                 }
-                Table{
+                Ok(Table{
                     schema: "".to_string(),
                     name: table.to_string(),
                     parent_table: None,
@@ -537,15 +800,13 @@ impl DatabaseDev for Sqlite{
                     comment: table_comment,
                     columns: columns,
                     is_view: false,
-                }
+                })
             },
-            Err(e) => {
-                panic!("No matching table found {}", e);
-            }
+            Err(e) => Err(e),
         }
     }
 
-    fn get_all_tables(&self)->Vec<(String, String, bool)>{
+    fn get_all_tables(&self)->Result<Vec<(String, String, bool)>, DbError>{
         let sql = "SELECT type, name, tbl_name, sql FROM sqlite_master WHERE type = 'table'";
         let result = self.execute_sql_with_return(&sql, &vec![]);
         match result{
@@ -557,21 +818,23 @@ impl DatabaseDev for Sqlite{
                     let is_view = false;
                     tables.push((schema, table, is_view))
                 }
-                tables 
+                Ok(tables)
             },
-            Err(e) => {
-                panic!("Unable to get tables due to {}", e)
-            }
+            Err(e) => Err(e),
         }
     }
 
-    fn get_inherited_columns(&self, schema:&str, table:&str)->Vec<String>{
-        vec![]
+    fn get_inherited_columns(&self, schema:&str, table:&str)->Result<Vec<String>, DbError>{
+        Ok(vec![])
     }
 
-    fn dbtype_to_rust_type(&self, db_type: &str)->(Vec<String>, String){panic!("not yet")}
+    fn dbtype_to_rust_type(&self, db_type: &str)->Result<(Vec<String>, String), DbError>{
+        Err(DbError::new("Converting sqlite data types to rust types is not yet supported"))
+    }
     
-    fn rust_type_to_dbtype(&self, rust_type: &str)->String{panic!("not yet")}
+    fn rust_type_to_dbtype(&self, rust_type: &str)->Result<String, DbError>{
+        Sqlite::rust_type_to_dbtype(self, rust_type)
+    }
 }
 
 
@@ -593,4 +856,33 @@ CREATE TABLE product_availability (
 )    
     ";
     Sqlite::extract_comments(create_sql);
-}
\ No newline at end of file
+}
+#[test]
+fn test_from_declared_type(){
+    use chrono::naive::date::NaiveDate;
+    let uuid = Sqlite::from_declared_type("uuid", Value::String("f7521093-734d-488a-9f60-fc9f11f7e750".to_string()));
+    assert_eq!(uuid.variant_name(), "Uuid");
+    assert_eq!(Sqlite::from_declared_type("boolean", Value::I64(1)), Value::Bool(true));
+    let date = Sqlite::from_declared_type("date", Value::String("2016-01-30".to_string()));
+    assert_eq!(date, Value::NaiveDate(NaiveDate::from_ymd(2016, 1, 30)));
+    let created = Sqlite::from_declared_type("timestamp", Value::String("2016-01-30 12:20:01".to_string()));
+    assert_eq!(created, Value::NaiveDateTime(NaiveDate::from_ymd(2016, 1, 30).and_hms(12, 20, 1)));
+    // not in the format of the declared type, kept as is
+    let open_time = Sqlite::from_declared_type("time with time zone", Value::String("08:00:00+08".to_string()));
+    assert_eq!(open_time, Value::String("08:00:00+08".to_string()));
+    assert_eq!(Sqlite::from_declared_type("integer", Value::I64(1)), Value::I64(1));
+    let price = Sqlite::from_declared_type("numeric(10,2)", Value::String("12.50".to_string()));
+    assert_eq!(price.to_string(), "'12.50'");
+    assert_eq!(price.variant_name(), "Decimal");
+    let info = Sqlite::from_declared_type("json", Value::String("{\"color\":\"red\"}".to_string()));
+    assert_eq!(info.variant_name(), "Json");
+}
+
+#[test]
+fn test_is_ddl(){
+    assert!(is_ddl("CREATE TABLE product(product_id integer)"));
+    assert!(is_ddl("  alter table product add column name text"));
+    assert!(is_ddl("DROP TABLE product"));
+    assert!(!is_ddl("SELECT * FROM product"));
+    assert!(!is_ddl("UPDATE product SET created = ?"));
+}
@@ -3,14 +3,14 @@ use r2d2_postgres::{PostgresConnectionManager};
 use r2d2::Config;
 use postgres::SslMode;
 use config::DbConfig;
-use database::{Database, DatabaseDDL, DatabaseDev};
+use database::{Database, DatabaseDDL, DatabaseDev, Transaction};
 use platform::Postgres;
 #[cfg(feature = "sqlite")]
 use platform::Sqlite;
 use platform::Mysql;
 use mysql::conn::pool::{MyPool};
 use mysql::conn::MyOpts;
-use database::DbError;
+use database::{DbError, DbErrorKind};
 
 #[cfg(feature = "sqlite")]
 use r2d2_sqlite::SqliteConnectionManager;
@@ -55,6 +55,12 @@ impl Platform{
             _ => panic!("others not yet..")
         }
     }
+
+    /// begin a transaction on this connection,
+    /// it is rolled back when dropped without being committed
+    pub fn transaction(&self)->Result<Transaction, DbError>{
+        Transaction::new(self.as_ref())
+    }
 }
 
 /// Postgres, Sqlite uses r2d2 connection manager,
@@ -85,7 +91,7 @@ impl ManagedPool{
                             Ok(pool) => Ok(ManagedPool::Postgres(pool)),
                             Err(e) => {
                                 println!("Unable to create a pool");
-                                Err(DbError::new(&format!("{}",e)))
+                                Err(DbError::with_cause(DbErrorKind::Connection, "Unable to create a pool", e))
                             }
                         }
                         
@@ -98,7 +104,7 @@ impl ManagedPool{
                             Ok(pool) => Ok(ManagedPool::Sqlite(pool)),
                             Err(e) => {
                                 println!("Unable to create a pool");
-                                Err(DbError::new(&format!("{}",e)))
+                                Err(DbError::with_cause(DbErrorKind::Connection, "Unable to create a pool", e))
                             }
                         }
                         
@@ -116,12 +122,12 @@ impl ManagedPool{
                             Ok(pool) => Ok(ManagedPool::Mysql(Some(pool))),
                             Err(e) => {
                                 println!("Unable to create a pool");
-                                Err(DbError::new(&format!("{}",e)))
+                                Err(DbError::with_cause(DbErrorKind::Connection, "Unable to create a pool", e))
                             }
                         }
                         
                     }
-                    _ => panic!("not yet")
+                    _ => Err(DbError::with_kind(DbErrorKind::Connection, &format!("Unsupported database platform: {}", platform))),
                 }
             },
             None => {
@@ -142,7 +148,7 @@ impl ManagedPool{
                         Ok(Platform::Postgres(pg))
                     },
                     Err(e) => {
-                        Err(DbError::new(&format!("Unable to connect due to {}", e)))
+                        Err(DbError::with_cause(DbErrorKind::Connection, "Unable to connect", e))
                     }
                 }
             },
@@ -154,7 +160,7 @@ impl ManagedPool{
                         Ok(Platform::Sqlite(lite))
                     },
                     Err(e) => {
-                        Err(DbError::new(&format!("Unable to connect due to {}", e)))
+                        Err(DbError::with_cause(DbErrorKind::Connection, "Unable to connect", e))
                     }
                 }
             },
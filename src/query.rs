@@ -1,6 +1,5 @@
 use dao::{Value, ToValue};
 use table::{Table};
-use std::collections::BTreeMap;
 use database::Database;
 use dao::DaoResult;
 use dao::IsDao;
@@ -8,6 +7,7 @@ use dao::Dao;
 use table::IsTable;
 use writer::SqlFrag;
 use std::fmt;
+use std::mem;
 use database::DbError;
 
 #[derive(Debug)]
@@ -41,17 +41,59 @@ pub enum Direction{
     DESC,
 }
 
+/// where the NULL values are placed in the ordering
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum NullsWhere{
+    FIRST,
+    LAST,
+}
 
-////
-/// Filter struct merged to query
-/// 
+/// an item in the ORDER BY clause
 #[derive(Debug)]
 #[derive(Clone)]
-pub enum Connector{
-    And,
-    Or
+pub struct Order{
+    pub operand:Operand,
+    pub direction:Direction,
+    /// the database default when not specified
+    pub nulls_where:Option<NullsWhere>,
 }
 
+impl Order{
+
+    pub fn new(operand:Operand, direction:Direction)->Self{
+        Order{operand:operand, direction:direction, nulls_where:None}
+    }
+
+    /// ascending order of the column, [schema.][table.]column
+    /// building the query fails when it is not a column name, use `Order::sql` for expressions
+    pub fn asc(column:&str)->Self{
+        Order::new(Operand::ColumnName(ColumnName::from_str(column)), Direction::ASC)
+    }
+
+    /// descending order of the column
+    pub fn desc(column:&str)->Self{
+        Order::new(Operand::ColumnName(ColumnName::from_str(column)), Direction::DESC)
+    }
+
+    /// order by an sql expression written as is, ie: coalesce(p.price, c.price)
+    /// the sql should not come from user input
+    pub fn sql(sql:&str, direction:Direction)->Self{
+        Order::new(Operand::Sql(sql.to_string()), direction)
+    }
+
+    pub fn nulls_first(mut self)->Self{
+        self.nulls_where = Some(NullsWhere::FIRST);
+        self
+    }
+
+    pub fn nulls_last(mut self)->Self{
+        self.nulls_where = Some(NullsWhere::LAST);
+        self
+    }
+}
+
+
 #[derive(Debug)]
 #[derive(Clone)]
 pub enum Equality{
@@ -66,6 +108,19 @@ pub enum Equality{
     LIKE,
     IS_NOT_NULL,//NOT_NULL,
     IS_NULL,//IS_NULL,
+    EXISTS,// EXISTS (subquery), no left operand
+    NOT_EXISTS,// NOT EXISTS (subquery), no left operand
+    ANY,// = ANY (array), the left operand is an element of the array
+    CONTAINS,// @> the left array or jsonb contains all the elements of the right
+    OVERLAPS,// && the arrays have elements in common
+    HAS_KEY,// ? the left json object has the right operand as key
+    BETWEEN,// BETWEEN low AND high, the right operand is a Vec of the 2 values
+    NOT_LIKE,
+    ILIKE,// case insensitive LIKE, lower(left) LIKE lower(right) when not supported
+    REGEX,// ~ matches the POSIX regular expression
+    IREGEX,// ~* matches the POSIX regular expression, case insensitive
+    IS_DISTINCT_FROM,// !=, where NULL is a comparable value
+    IS_NOT_DISTINCT_FROM,// =, where NULL is a comparable value
 }
 
 /// function in a sql statement
@@ -76,6 +131,98 @@ pub struct Function{
     pub params:Vec<Operand>,
 }
 
+impl Function{
+
+    pub fn new(function:&str, params:Vec<Operand>)->Self{
+        Function{function:function.to_string(), params:params}
+    }
+
+    /// function which has columns as parameters ie: lower(name), COUNT(*)
+    pub fn with_columns(function:&str, columns:Vec<&str>)->Self{
+        let mut params = vec![];
+        for c in columns{
+            params.push(Operand::ColumnName(ColumnName::from_str(c)));
+        }
+        Function::new(function, params)
+    }
+}
+
+/// the commonly used aggregate functions
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum Aggregate{
+    COUNT,
+    SUM,
+    AVG,
+    MIN,
+    MAX,
+}
+
+impl Aggregate{
+
+    /// the sql function name of this aggregate
+    pub fn function_name(&self)->&str{
+        match *self{
+            Aggregate::COUNT => "COUNT",
+            Aggregate::SUM => "SUM",
+            Aggregate::AVG => "AVG",
+            Aggregate::MIN => "MIN",
+            Aggregate::MAX => "MAX",
+        }
+    }
+}
+
+/// a value inside a json column, following the path of keys and array indexes
+/// ie: info->'dimension'->>'unit'
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct JsonPath{
+    pub column:ColumnName,
+    pub path:Vec<JsonSegment>,
+    /// extract the value as text (->>), otherwise as json (->)
+    pub as_text:bool,
+}
+
+/// a step in the json path
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum JsonSegment{
+    /// the key of an object, numeric keys such as "2024" are still keys
+    Key(String),
+    /// the index of an array
+    Index(u32),
+}
+
+impl JsonPath{
+
+    /// the value at the path of object keys as text, ie: info->>'color'
+    pub fn text(column:&str, path:Vec<&str>)->Self{
+        JsonPath{
+            column:ColumnName::from_str(column),
+            path:path.iter().map(|p| JsonSegment::Key(p.to_string())).collect(),
+            as_text:true,
+        }
+    }
+
+    /// the value at the path of object keys as json, ie: info->'dimension'
+    pub fn json(column:&str, path:Vec<&str>)->Self{
+        JsonPath{as_text:false, ..JsonPath::text(column, path)}
+    }
+
+    /// continue the path to the key of the object, ie: info->'dimension'->'unit'
+    pub fn key(mut self, key:&str)->Self{
+        self.path.push(JsonSegment::Key(key.to_string()));
+        self
+    }
+
+    /// continue the path to the element of the array, ie: info->'sizes'->0
+    pub fn index(mut self, index:u32)->Self{
+        self.path.push(JsonSegment::Index(index));
+        self
+    }
+}
+
 /// Operands can be columns, functions, query or value types
 #[derive(Debug)]
 #[derive(Clone)]
@@ -86,6 +233,9 @@ pub enum Operand{
     Query(Query),
     Value(Value),
     Vec(Vec<Operand>),
+    JsonPath(JsonPath),
+    /// an sql expression written as is, such as coalesce(p.price, c.price)
+    Sql(String),
 }
 
 /// expression has left operand,
@@ -98,56 +248,109 @@ pub struct Condition{
     pub right:Operand,
 }
 
-/// TODO: support for functions on columns
-/// TODO: need to merge with Expr
+/// how a subfilter was connected to its parent when Filter was a struct,
+/// Filter is now an expression tree, use Filter::all, Filter::any, and_filter and or_filter instead
+#[deprecated(note = "Filter is an enum now, use Filter::all, Filter::any, and_filter or or_filter")]
 #[derive(Debug)]
 #[derive(Clone)]
-pub struct Filter{
-    pub connector:Connector,
-    /// TODO: maybe renamed to LHS, supports functions and SQL
-    pub condition: Condition,
-    pub subfilters:Vec<Filter>
+pub enum Connector{
+    And,
+    Or
+}
+
+/// boolean expression tree of the conditions.
+/// this replaces the former struct with `connector`, `condition` and `subfilters` fields, code matching on
+/// those fields has to match the variants instead, the constructors `new`, `with_value` and `bare_new` are unchanged
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum Filter{
+    Condition(Condition),
+    /// all of the filters should be true
+    All(Vec<Filter>),
+    /// any of the filters should be true
+    Any(Vec<Filter>),
+    Not(Box<Filter>),
 }
 
 impl Filter{
     
     /// user friendly, commonly use API
     pub fn new(column:&str, equality:Equality, value:&ToValue)->Self{
-        let right = Operand::Value(value.to_db_type());
-        Filter{
-            connector:Connector::And,
-            condition: Condition{left: Operand::ColumnName(ColumnName::from_str(column)),
-                        equality:equality,
-                        right:right},
-            subfilters:vec![],
-        }
+        Filter::with_value(column, equality, value.to_db_type())
     }
     
     /// user friendly, commonly use API
     pub fn with_value(column:&str, equality:Equality, value: Value)->Self{
-        let right = Operand::Value(value);
-        Filter{
-            connector:Connector::And,
-            condition: Condition{left: Operand::ColumnName(ColumnName::from_str(column)),
-                        equality:equality,
-                        right:right},
-            subfilters:vec![],
-        }
+        Filter::bare_new(Operand::ColumnName(ColumnName::from_str(column)), equality, Operand::Value(value))
     }
     
     
     /// not very commonly used, offers enough flexibility
     pub fn bare_new(left: Operand, equality: Equality, right: Operand)->Self{
-        Filter{
-            connector:Connector::And,
-            condition: Condition{left:left,
+        Filter::Condition(Condition{left:left,
                         equality:equality,
-                        right:right},
-            subfilters:vec![],
-        }
+                        right:right})
+    }
+
+    /// NOT (filter)
+    pub fn not(filter: Filter)->Self{
+        Filter::Not(Box::new(filter))
+    }
+
+    /// filter1 AND filter2 AND ...
+    pub fn all(filters: Vec<Filter>)->Self{
+        Filter::All(filters)
+    }
+
+    /// filter1 OR filter2 OR ...
+    pub fn any(filters: Vec<Filter>)->Self{
+        Filter::Any(filters)
     }
     
-    
+    /// column IN (subquery)
+    pub fn in_query(column:&str, query:Query)->Self{
+        let left = Operand::ColumnName(ColumnName::from_str(column));
+        Filter::bare_new(left, Equality::IN, Operand::Query(query))
+    }
+
+    /// column NOT IN (subquery)
+    pub fn not_in_query(column:&str, query:Query)->Self{
+        let left = Operand::ColumnName(ColumnName::from_str(column));
+        Filter::bare_new(left, Equality::NOT_IN, Operand::Query(query))
+    }
+
+    /// EXISTS (subquery), the left operand is not used
+    pub fn exists(query:Query)->Self{
+        Filter::bare_new(Operand::Vec(vec![]), Equality::EXISTS, Operand::Query(query))
+    }
+
+    /// NOT EXISTS (subquery), the left operand is not used
+    pub fn not_exists(query:Query)->Self{
+        Filter::bare_new(Operand::Vec(vec![]), Equality::NOT_EXISTS, Operand::Query(query))
+    }
+
+    /// compare the result of the function, ie: COUNT(*) > 5
+    pub fn function(function:Function, equality:Equality, value:&ToValue)->Self{
+        Filter::bare_new(Operand::Function(function), equality, Operand::Value(value.to_db_type()))
+    }
+
+    /// compare the value at the json path, ie: info->>'color' = 'red'
+    pub fn json_path(path:JsonPath, equality:Equality, value:&ToValue)->Self{
+        Filter::bare_new(Operand::JsonPath(path), equality, Operand::Value(value.to_db_type()))
+    }
+
+    /// the json object in the column has the key, ie: info ? 'weight'
+    pub fn has_key(column:&str, key:&str)->Self{
+        Filter::new(column, Equality::HAS_KEY, &key)
+    }
+
+    /// column BETWEEN low AND high
+    pub fn between(column:&str, low:&ToValue, high:&ToValue)->Self{
+        let left = Operand::ColumnName(ColumnName::from_str(column));
+        let right = Operand::Vec(vec![Operand::Value(low.to_db_type()), Operand::Value(high.to_db_type())]);
+        Filter::bare_new(left, Equality::BETWEEN, right)
+    }
+
     pub fn is_null(column:&str)->Self{
         Filter::new(column, Equality::IS_NULL, &())
     }
@@ -156,29 +359,67 @@ impl Filter{
     }
     
     pub fn and(&mut self, column:&str, equality:Equality, value:&ToValue)->&mut Self{
-        let mut filter = Filter::new(column, equality, value);
-        filter.connector = Connector::And;
-        self.subfilters.push(filter);
-        self
+        self.and_filter(Filter::new(column, equality, value))
     }
     
     pub fn or(&mut self, column:&str, equality:Equality, value:&ToValue)->&mut Self{
-        let mut filter = Filter::new(column, equality, value);
-        filter.connector = Connector::Or;
-        self.subfilters.push(filter);
-        self
+        self.or_filter(Filter::new(column, equality, value))
     }
     
+    /// an All or Any without filters, which has no filter to combine with
+    fn is_empty_group(&self)->bool{
+        match *self{
+            Filter::All(ref filters) | Filter::Any(ref filters) => filters.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// the chained filters follow the sql precedence, AND binds tighter than OR
+    /// ie: a.or_filter(b).and_filter(c) is a OR (b AND c),
+    /// use Filter::any and Filter::all to group the filters explicitly.
+    /// an empty group is left out when combined, instead of becoming a true or false predicate
     pub fn or_filter(&mut self, filter: Filter)->&mut Self{
-        let mut filter = filter.clone();
-        filter.connector = Connector::Or;
-        self.subfilters.push(filter);
+        if filter.is_empty_group(){
+            return self;
+        }
+        if self.is_empty_group(){
+            *self = filter;
+            return self;
+        }
+        let this = mem::replace(self, Filter::Any(vec![]));
+        *self = match this{
+            Filter::Any(mut filters) => {
+                filters.push(filter);
+                Filter::Any(filters)
+            },
+            this => Filter::Any(vec![this, filter]),
+        };
         self
     }
+    /// AND the filter to the last operand of an OR, keeping the sql precedence
     pub fn and_filter(&mut self, filter: Filter)->&mut Self{
-        let mut filter = filter.clone();
-        filter.connector = Connector::And;
-        self.subfilters.push(filter);
+        if filter.is_empty_group(){
+            return self;
+        }
+        if self.is_empty_group(){
+            *self = filter;
+            return self;
+        }
+        let this = mem::replace(self, Filter::All(vec![]));
+        *self = match this{
+            Filter::All(mut filters) => {
+                filters.push(filter);
+                Filter::All(filters)
+            },
+            Filter::Any(mut filters) => {
+                if let Some(mut last) = filters.pop(){
+                    last.and_filter(filter);
+                    filters.push(last);
+                }
+                Filter::Any(filters)
+            },
+            this => Filter::All(vec![this, filter]),
+        };
         self
     }
 }
@@ -231,16 +472,19 @@ impl Field{
 
 impl ColumnName{
 
+    /// parse the column as [schema.][table.]column
+    /// the name is not validated, the leading parts of a name with more than 3 parts are kept as the schema,
+    /// see `try_from_str`
     pub fn from_str(column:&str)->Self{
         if column.contains("."){
             let splinters = column.split(".").collect::<Vec<&str>>();
-            assert!(splinters.len() == 2, "There should only be 2 splinters");
-            let table_split = splinters[0].to_string();
-            let column_split = splinters[1].to_string();
+            let schema_split = if splinters.len() > 2 { Some(splinters[..splinters.len() - 2].join(".")) } else { None };
+            let table_split = splinters[splinters.len() - 2].to_string();
+            let column_split = splinters[splinters.len() - 1].to_string();
             ColumnName{
-                column:column_split.to_string(), 
-                table:Some(table_split.to_string()), 
-                schema:None,
+                column:column_split, 
+                table:Some(table_split), 
+                schema:schema_split,
             }
         } else {
             ColumnName{
@@ -251,6 +495,23 @@ impl ColumnName{
         }
     }
     
+    /// parse the column as [schema.][table.]column,
+    /// errors when it is not a column name such as an sql expression
+    pub fn try_from_str(column:&str)->Result<Self, DbError>{
+        if ColumnName::is_column_name(column){
+            Ok(ColumnName::from_str(column))
+        }else{
+            Err(DbError::new(&format!("{} is not a column name", column)))
+        }
+    }
+
+    /// is this a plain [schema.][table.]column, and not an expression
+    pub fn is_column_name(column:&str)->bool{
+        let splinters = column.split(".").collect::<Vec<&str>>();
+        splinters.len() <= 3
+            && splinters.iter().all(|s| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    }
+
     fn default_rename(&self)->String{
          if self.table.is_some(){
             return format!("{}_{}", self.table.as_ref().unwrap(), self.column);
@@ -399,6 +660,21 @@ impl ToTableName for Table{
     }
 }
 
+/// a query declared in the WITH clause (common table expression) of the query
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct DeclaredQuery{
+    /// the name the main query refers to
+    pub alias: String,
+    /// the non-recursive term
+    pub query: Query,
+    /// the recursive term, which refers back to the alias
+    pub recursive_query: Option<Query>,
+    /// join the terms with UNION ALL, otherwise UNION which removes the duplicate records,
+    /// stopping the recursion on hierarchies that has cycles
+    pub union_all: bool,
+}
+
 /// Query Error
 pub enum Error{
     NoTableSpecified(String),
@@ -419,7 +695,9 @@ pub struct Query{
     /// whether to enumate all columns in involved models
     pub enumerate_all: bool,
     
-    pub declared_query: BTreeMap<String, Query>,
+    /// queries declared in the WITH clause, kept in the order they are declared
+    /// since a declared query can only refer to the ones declared before it
+    pub declared_query: Vec<DeclaredQuery>,
 
     ///fields can be functions, column sql query, and even columns
     /// TODO; merge enumerated column to this, add a builder for fields
@@ -437,13 +715,13 @@ pub struct Query{
     /// ordering of the records via the columns specified
     /// TODO: ordering should be more flexible than this
     /// needs to support expressions
-    pub order_by:Vec<(String, Direction)>,
+    pub order_by:Vec<Order>,
     
     /// grouping columns to create an aggregate
     pub group_by: Vec<Operand>,
     
-    /// having field
-    pub having: Vec<Condition>,
+    /// having field, the filters on the grouped records
+    pub having: Vec<Filter>,
     
     /// exclude the mention of the columns in the SQL query, useful when ignoring changes in update/insert records
     pub excluded_columns:Vec<ColumnName>,
@@ -467,6 +745,10 @@ pub struct Query{
     
     /// the returning clause of the query when supported,
     pub enumerated_returns: Vec<Field>,
+
+    /// the columns which identifies an existing record when inserting,
+    /// when specified the insert becomes an upsert, updating the existing record instead
+    pub conflict_columns: Vec<ColumnName>,
 }
 
 impl Query{
@@ -477,7 +759,7 @@ impl Query{
             sql_type:SqlType::SELECT,
             distinct:false,
             enumerate_all: false,
-            declared_query: BTreeMap::new(),
+            declared_query: vec![],
             enumerated_fields: vec![],
             distinct_on_columns: vec![],
             filters: vec![],
@@ -491,6 +773,7 @@ impl Query{
             from: None,
             values:vec![],
             enumerated_returns: vec![],
+            conflict_columns: vec![],
         }
     }
     
@@ -563,6 +846,58 @@ impl Query{
         self
     }
     
+    /// add a field, such as functions and sub queries, to the enumerated fields
+    pub fn add_field(&mut self, field:Field)->&mut Self{
+        self.enumerated_fields.push(field);
+        self
+    }
+
+    /// enumerate the result of a function, renamed to alias
+    /// ie: lower(name) AS lower_name
+    pub fn function(&mut self, function:Function, alias:&str)->&mut Self{
+        let field = Field{operand: Operand::Function(function), name: Some(alias.to_string())};
+        self.add_field(field)
+    }
+
+    /// enumerate the value at the json path, renamed to alias
+    /// ie: info->>'color' AS color
+    pub fn json_path(&mut self, path:JsonPath, alias:&str)->&mut Self{
+        let field = Field{operand: Operand::JsonPath(path), name: Some(alias.to_string())};
+        self.add_field(field)
+    }
+
+    /// enumerate an aggregate of the column, renamed to alias
+    /// ie: SUM(price) AS total_price
+    pub fn aggregate(&mut self, aggregate:Aggregate, column:&str, alias:&str)->&mut Self{
+        let function = Function::with_columns(aggregate.function_name(), vec![column]);
+        self.function(function, alias)
+    }
+
+    /// COUNT(column) AS alias, use `*` to count all the records
+    pub fn count(&mut self, column:&str, alias:&str)->&mut Self{
+        self.aggregate(Aggregate::COUNT, column, alias)
+    }
+
+    /// SUM(column) AS alias
+    pub fn sum(&mut self, column:&str, alias:&str)->&mut Self{
+        self.aggregate(Aggregate::SUM, column, alias)
+    }
+
+    /// AVG(column) AS alias
+    pub fn avg(&mut self, column:&str, alias:&str)->&mut Self{
+        self.aggregate(Aggregate::AVG, column, alias)
+    }
+
+    /// MIN(column) AS alias
+    pub fn min(&mut self, column:&str, alias:&str)->&mut Self{
+        self.aggregate(Aggregate::MIN, column, alias)
+    }
+
+    /// MAX(column) AS alias
+    pub fn max(&mut self, column:&str, alias:&str)->&mut Self{
+        self.aggregate(Aggregate::MAX, column, alias)
+    }
+
     pub fn group_by(&mut self, columns:Vec<&str>)->&mut Self{
         for c in columns{
             let column_name = ColumnName::from_str(c);
@@ -573,16 +908,25 @@ impl Query{
     }
     
     pub fn having(&mut self, column:&str, equality: Equality, value :&ToValue)->&mut Self{
-        let column_name = ColumnName::from_str(column);
-        let left = Operand::ColumnName(column_name);
-        let cond = Condition{
-            left: left,
-            equality: equality,
-            right: Operand::Value(value.to_db_type())
-        };
-        self.having.push(cond);
+        self.add_having(Filter::new(column, equality, value))
+    }
+
+    /// filter the grouped records, combined with the other having filters with AND
+    pub fn add_having(&mut self, filter:Filter)->&mut Self{
+        self.having.push(filter);
         self
     }
+
+    /// ie: HAVING SUM(price) > value
+    pub fn having_function(&mut self, function:Function, equality: Equality, value :&ToValue)->&mut Self{
+        self.add_having(Filter::function(function, equality, value))
+    }
+
+    /// ie: HAVING COUNT(*) > value
+    pub fn having_aggregate(&mut self, aggregate:Aggregate, column:&str, equality: Equality, value :&ToValue)->&mut Self{
+        let function = Function::with_columns(aggregate.function_name(), vec![column]);
+        self.having_function(function, equality, value)
+    }
     
     /// exclude columns when inserting/updating data
     /// also ignores the column when selecting records
@@ -657,14 +1001,54 @@ impl Query{
     
     /// if the database support CTE declareted query i.e WITH, 
     /// then this query will be declared
-    /// if database doesn't support WITH queries, then this query will be 
-    /// wrapped in the from_query
-    /// build a builder for this
+    /// if database doesn't support WITH queries, building the query will return an error
     pub fn declare_query(&mut self, query:Query, alias:&str)->&mut Self{
-        self.declared_query.insert(alias.to_string(), query);
+        let declared = DeclaredQuery{
+            alias: alias.to_string(),
+            query: query,
+            recursive_query: None,
+            union_all: false,
+        };
+        self.declared_query.push(declared);
         self
     }
     
+    /// declare a recursive query i.e WITH RECURSIVE alias AS (query UNION ALL recursive_query)
+    /// the recursive_query joins back to the alias, useful for walking self referencing tables
+    pub fn declare_recursive_query(&mut self, query:Query, recursive_query:Query, alias:&str)->&mut Self{
+        let declared = DeclaredQuery{
+            alias: alias.to_string(),
+            query: query,
+            recursive_query: Some(recursive_query),
+            union_all: true,
+        };
+        self.declared_query.push(declared);
+        self
+    }
+
+    /// declare a recursive query i.e WITH RECURSIVE alias AS (query UNION recursive_query)
+    /// the records already found are not visited again, use this when the hierarchy can have cycles
+    pub fn declare_recursive_query_distinct(&mut self, query:Query, recursive_query:Query, alias:&str)->&mut Self{
+        let declared = DeclaredQuery{
+            alias: alias.to_string(),
+            query: query,
+            recursive_query: Some(recursive_query),
+            union_all: false,
+        };
+        self.declared_query.push(declared);
+        self
+    }
+    
+    /// whether any of the declared queries is recursive
+    pub fn has_recursive_declared_query(&self)->bool{
+        for declared in &self.declared_query{
+            if declared.recursive_query.is_some(){
+                return true;
+            }
+        }
+        false
+    }
+    
     /// a query to query from
     /// use WITH (query) t1 SELECT from t1 declaration in postgresql, sqlite
     /// use SELECT FROM (query) in oracle, mysql, others 
@@ -694,6 +1078,33 @@ impl Query{
         }
     }
     
+
+    /// determine if the column belongs to the table this query is selecting from,
+    /// columns that don't are referring to the outer query as in correlated subqueries.
+    /// when the table is aliased, the column should refer to the alias,
+    /// otherwise to the table name, in the same schema when the column has one
+    pub fn is_own_column(&self, column_name:&ColumnName)->bool{
+        let table = match column_name.table{
+            Some(ref table) => table,
+            None => return true,
+        };
+        let from = match self.from{
+            Some(ref from) => from,
+            None => return true,
+        };
+        match from.name{
+            Some(ref alias) => column_name.schema.is_none() && alias == table,
+            None => {
+                match from.operand{
+                    Operand::TableName(ref from_table) => {
+                        &from_table.name == table
+                            && (column_name.schema.is_none() || column_name.schema == from_table.schema)
+                    },
+                    _ => true,
+                }
+            },
+        }
+    }
     
     /// join a table on this query
     ///
@@ -759,15 +1170,40 @@ impl Query{
         self.join(join)
     }
     
+    /// add an item to the ORDER BY clause, such as functions and NULLS FIRST/LAST
+    pub fn add_order(&mut self, order:Order)->&mut Self{
+        self.order_by.push(order);
+        self
+    }
+
     ///ascending orderby of this column
     pub fn asc(&mut self, column:&str)->&mut Self{
-        self.order_by.push((column.to_string(), Direction::ASC));
-        self
+        self.add_order(Order::asc(column))
     }
-        ///ascending orderby of this column
+    ///descending orderby of this column
     pub fn desc(&mut self, column:&str)->&mut Self{
-        self.order_by.push((column.to_string(), Direction::DESC));
-        self
+        self.add_order(Order::desc(column))
+    }
+    /// ascending orderby of this column, NULL values first
+    pub fn asc_nulls_first(&mut self, column:&str)->&mut Self{
+        self.add_order(Order::asc(column).nulls_first())
+    }
+    /// ascending orderby of this column, NULL values last
+    pub fn asc_nulls_last(&mut self, column:&str)->&mut Self{
+        self.add_order(Order::asc(column).nulls_last())
+    }
+    /// descending orderby of this column, NULL values first
+    pub fn desc_nulls_first(&mut self, column:&str)->&mut Self{
+        self.add_order(Order::desc(column).nulls_first())
+    }
+    /// descending orderby of this column, NULL values last
+    pub fn desc_nulls_last(&mut self, column:&str)->&mut Self{
+        self.add_order(Order::desc(column).nulls_last())
+    }
+    /// order by an sql expression written as is, ie: coalesce(p.price, c.price)
+    /// the column names in `asc` and `desc` are checked, the sql here is not and should not come from user input
+    pub fn order_by_sql(&mut self, sql:&str, direction:Direction)->&mut Self{
+        self.add_order(Order::sql(sql, direction))
     }
     
     /// get the indexes of the fields that matches the the column name
@@ -929,7 +1365,18 @@ impl Query{
     fn remove_from_enumerated(&mut self, column_name: &ColumnName)->&mut Self{
         let index = self.index_of_field(column_name);
         if index.is_some(){
-            self.enumerated_fields.remove(index.unwrap());
+            let index = index.unwrap();
+            let column_count = self.enumerated_fields.len();
+            self.enumerated_fields.remove(index);
+            // the values when inserting/updating are laid out in rows of the enumerated columns,
+            // the value of the removed column is removed from each row as well
+            if !self.values.is_empty() && self.values.len() % column_count == 0{
+                let values = self.values.clone();
+                self.values = values.into_iter().enumerate()
+                    .filter(|&(i, _)| i % column_count != index)
+                    .map(|(_, v)| v)
+                    .collect();
+            }
         }
         self
     }
@@ -987,6 +1434,42 @@ impl Query{
     pub fn filter_gte(&mut self, column:&str, value:&ToValue)->&mut Self{
         self.add_filter(Filter::new(column, Equality::GTE, value))
     }
+
+    /// column BETWEEN low AND high
+    pub fn filter_between(&mut self, column:&str, low:&ToValue, high:&ToValue)->&mut Self{
+        self.add_filter(Filter::between(column, low, high))
+    }
+
+    /// column LIKE value
+    pub fn filter_like(&mut self, column:&str, value:&ToValue)->&mut Self{
+        self.add_filter(Filter::new(column, Equality::LIKE, value))
+    }
+    /// column NOT LIKE value
+    pub fn filter_not_like(&mut self, column:&str, value:&ToValue)->&mut Self{
+        self.add_filter(Filter::new(column, Equality::NOT_LIKE, value))
+    }
+    /// column ILIKE value
+    pub fn filter_ilike(&mut self, column:&str, value:&ToValue)->&mut Self{
+        self.add_filter(Filter::new(column, Equality::ILIKE, value))
+    }
+
+    /// column ~ value
+    pub fn filter_regex(&mut self, column:&str, value:&ToValue)->&mut Self{
+        self.add_filter(Filter::new(column, Equality::REGEX, value))
+    }
+    /// column ~* value
+    pub fn filter_iregex(&mut self, column:&str, value:&ToValue)->&mut Self{
+        self.add_filter(Filter::new(column, Equality::IREGEX, value))
+    }
+
+    /// column IS DISTINCT FROM value
+    pub fn filter_distinct_from(&mut self, column:&str, value:&ToValue)->&mut Self{
+        self.add_filter(Filter::new(column, Equality::IS_DISTINCT_FROM, value))
+    }
+    /// column IS NOT DISTINCT FROM value
+    pub fn filter_not_distinct_from(&mut self, column:&str, value:&ToValue)->&mut Self{
+        self.add_filter(Filter::new(column, Equality::IS_NOT_DISTINCT_FROM, value))
+    }
     
     pub fn add_value(&mut self, value:Operand)->&mut Self{
         self.values.push(value);
@@ -998,12 +1481,57 @@ impl Query{
         self.add_value(operand)
     }
     
+    /// the values are laid out row after row when bulk inserting,
+    /// split the rows into several queries such that each query will not exceed max_params,
+    /// a single row that needs more than max_params can not be inserted and returns an error
+    pub fn split_values(&self, max_params:usize)->Result<Vec<Query>, DbError>{
+        let column_count = self.get_enumerated_columns().len();
+        if column_count > max_params{
+            return Err(DbError::new(&format!("Inserting {} columns exceeds the maximum of {} parameters per query",
+                column_count, max_params)));
+        }
+        if column_count == 0 || self.values.len() <= max_params{
+            return Ok(vec![self.clone()]);
+        }
+        let rows_per_query = max_params / column_count;
+        let mut template = self.clone();
+        template.values.clear();
+        let mut queries = vec![];
+        for chunk in self.values.chunks(rows_per_query * column_count){
+            let mut query = template.clone();
+            query.values = chunk.to_vec();
+            queries.push(query);
+        }
+        Ok(queries)
+    }
+
     /// set a value of a column when inserting/updating records
     pub fn set(&mut self, column: &str, value:&ToValue)->&mut Self{
         self.column(column);
         self.value(value)
     }
     
+    /// turn the insert into an upsert, when a record with the same values on these columns
+    /// already exists, the rest of the columns of that record is updated instead
+    pub fn on_conflict(&mut self, columns: Vec<&str>)->&mut Self{
+        for c in columns{
+            self.conflict_columns.push(ColumnName::from_str(c));
+        }
+        self
+    }
+
+    pub fn is_upsert(&self)->bool{
+        !self.conflict_columns.is_empty()
+    }
+
+    /// the enumerated columns which are not part of the conflict columns,
+    /// these are the ones updated when the record already exists
+    pub fn get_conflict_update_columns(&self)->Vec<&ColumnName>{
+        self.get_enumerated_columns().into_iter()
+            .filter(|c| !self.conflict_columns.iter().any(|cc| cc.column == c.column))
+            .collect()
+    }
+
      pub fn return_all(&mut self)->&mut Self{
         self.enumerate_column_as_return("*")
     }
@@ -1024,7 +1552,7 @@ impl Query{
     }
     
     /// build the query only, not executed, useful when debugging
-    pub fn build(&mut self, db: &Database)->SqlFrag{
+    pub fn build(&mut self, db: &Database)->Result<SqlFrag, DbError>{
         self.finalize();
         db.build_query(self)
     }
@@ -1051,20 +1579,16 @@ impl Query{
     
     /// execute the query, then convert the result
     pub fn collect<T: IsDao+IsTable>(&mut self, db: &Database)->Result<Vec<T>, DbError>{
-        let result = self.retrieve(db);
-        match result{
-            Ok(result) => Ok(result.cast()),
-            Err(e) => Err(DbError::new("Error in the query"))
-        }
+        let result = try!(self.retrieve(db));
+        result.cast()
     }
     
     /// execute the query then collect only 1 record
-    /// TODO: use Result<T,Error> instead of Option<T>
     pub fn collect_one<T: IsDao+IsTable>(&mut self, db: &Database)->Result<T, DbError>{
-        let result = self.retrieve(db);
-        match result{
-            Ok(result) => Ok(result.cast_one().unwrap()),
-            Err(e) => Err(DbError::new("Error in the query"))
+        let result = try!(self.retrieve(db));
+        match try!(result.cast_one()){
+            Some(one) => Ok(one),
+            None => Err(DbError::not_found("There is no record returned")),
         }
     }
 }
@@ -22,6 +22,10 @@ pub struct Column{
     pub foreign:Option<Foreign>,
     ///determines if the column is inherited from the parent table
     pub is_inherited:bool,
+    /// the total number of digits of numeric columns, ie: 10 of numeric(10,2)
+    pub precision:Option<u32>,
+    /// the number of digits after the decimal point of numeric columns, ie: 2 of numeric(10,2)
+    pub scale:Option<u32>,
 }
 
 impl Column{
@@ -32,6 +36,25 @@ impl Column{
     }
 
 
+    /// extract the precision and scale from the numeric and decimal database data types,
+    /// ie: numeric(10,2) is (Some(10), Some(2)), decimal(10) is (Some(10), Some(0))
+    pub fn precision_and_scale(db_data_type:&str)->(Option<u32>, Option<u32>){
+        let db_data_type = db_data_type.trim().to_lowercase();
+        if !db_data_type.starts_with("numeric") && !db_data_type.starts_with("decimal"){
+            return (None, None);
+        }
+        let size = match (db_data_type.find('('), db_data_type.find(')')){
+            (Some(open), Some(close)) if open < close => &db_data_type[open + 1..close],
+            _ => return (None, None),
+        };
+        let mut parts = size.split(',').map(|p| p.trim().parse::<u32>().ok());
+        match (parts.next(), parts.next()){
+            (Some(Some(precision)), None) => (Some(precision), Some(0)),
+            (Some(Some(precision)), Some(Some(scale))) => (Some(precision), Some(scale)),
+            _ => (None, None),
+        }
+    }
+
     ///some column names may be a rust reserve keyword, so have to correct them
     pub fn corrected_name(&self)->String{
         if Self::is_keyword(&self.name){
@@ -697,3 +720,11 @@ fn capitalize(str:&str)->String{
 fn test_capitalize(){
     assert_eq!(capitalize("hello"), "Hello".to_string());
 }
+
+#[test]
+fn test_precision_and_scale(){
+    assert_eq!(Column::precision_and_scale("numeric(10,2)"), (Some(10), Some(2)));
+    assert_eq!(Column::precision_and_scale("DECIMAL(10)"), (Some(10), Some(0)));
+    assert_eq!(Column::precision_and_scale("numeric"), (None, None));
+    assert_eq!(Column::precision_and_scale("character varying(255)"), (None, None));
+}
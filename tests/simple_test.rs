@@ -13,6 +13,7 @@ use rustorm::platform::postgres::Postgres;
 use rustorm::query::Query;
 use rustorm::query::{Filter,Equality};
 use rustorm::dao::{Dao,IsDao};
+use rustorm::database::DbError;
 use rustorm::pool::ManagedPool;
 use rustorm::table::{IsTable,Table};
 
@@ -26,12 +27,12 @@ pub struct Product {
 }
 
 impl IsDao for Product{
-    fn from_dao(dao:&Dao)->Self{
-        Product{
-            product_id: dao.get("product_id"),
-            name: dao.get_opt("name"),
-            description: dao.get_opt("description"),
-        }
+    fn from_dao(dao:&Dao)->Result<Self, DbError>{
+        Ok(Product{
+            product_id: try!(dao.try_get("product_id")),
+            name: try!(dao.try_get_opt("name")),
+            description: try!(dao.try_get_opt("description")),
+        })
     }
     fn to_dao(&self)->Dao{
         let mut dao = Dao::new();
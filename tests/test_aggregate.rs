@@ -0,0 +1,76 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+use rustorm::query::{Equality, Function, Filter, Aggregate};
+
+
+#[test]
+fn test_aggregate_fields(){
+    let db = Postgres::new();
+    let mut query = Query::select();
+    query.column("currency_id")
+        .count("*", "product_count")
+        .sum("price", "total_price")
+        .max("price", "highest_price")
+        .from_table("bazaar.product")
+        .filter("active", Equality::EQ, &true)
+        .group_by(vec!["currency_id"])
+        .having_aggregate(Aggregate::COUNT, "*", Equality::GT, &5);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT currency_id, COUNT(*) AS product_count, SUM(price) AS total_price, 
+          MAX(price) AS highest_price
+     FROM bazaar.product
+    WHERE active = $1 
+ GROUP BY currency_id 
+   HAVING COUNT(*) > $2 ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_function_field(){
+    let db = Postgres::new();
+    let mut query = Query::select();
+    query.column("product_id")
+        .function(Function::with_columns("lower", vec!["name"]), "lower_name")
+        .from_table("bazaar.product");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT product_id, lower(name) AS lower_name
+     FROM bazaar.product".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_having_filters(){
+    let db = Mysql::new();
+    let mut high = Filter::function(Function::with_columns("SUM", vec!["price"]), Equality::GT, &1000);
+    high.or_filter(Filter::function(Function::with_columns("MAX", vec!["price"]), Equality::GT, &500));
+    let mut query = Query::select();
+    query.column("currency_id")
+        .count("*", "product_count")
+        .from_table("product")
+        .group_by(vec!["currency_id"])
+        .having_aggregate(Aggregate::COUNT, "*", Equality::GT, &5)
+        .add_having(high);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT currency_id, COUNT(*) AS product_count
+     FROM product
+ GROUP BY currency_id 
+   HAVING COUNT(*) > ?
+      AND ( SUM(price) > ? OR MAX(price) > ? )".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+    assert_eq!(frag.params.len(), 3);
+}
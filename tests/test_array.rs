@@ -0,0 +1,39 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+use rustorm::query::Equality;
+use rustorm::dao::Value;
+
+
+#[test]
+fn test_array_filters(){
+    let db = Postgres::new();
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .filter("name", Equality::ANY, &vec!["GTX660".to_string(), "GTX680".to_string()])
+        .filter("tags", Equality::CONTAINS, &vec!["gpu".to_string()])
+        .filter("tags", Equality::OVERLAPS, &vec!["nvidia".to_string(), "amd".to_string()]);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+    WHERE name = ANY($1 )
+      AND tags @> $2 
+      AND tags && $3 ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+    assert_eq!(frag.params[1], Value::Array(vec![Value::String("gpu".to_string())]));
+}
+
+#[test]
+fn test_array_filters_unsupported(){
+    let db = Mysql::new();
+    let mut query = Query::select_all();
+    query.from_table("product")
+        .filter("tags", Equality::CONTAINS, &vec!["gpu".to_string()]);
+    assert!(query.build(&db).is_err());
+}
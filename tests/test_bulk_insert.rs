@@ -0,0 +1,65 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::query::Query;
+
+
+#[test]
+fn test_bulk_insert(){
+    let db = Postgres::new();
+    let mut query = Query::insert();
+    query.into_table("bazaar.photo")
+        .columns(vec!["url", "seq_no"])
+        .value(&"img1.jpg").value(&1)
+        .value(&"img2.jpg").value(&2)
+        .value(&"img3.jpg").value(&3)
+        .returns(vec!["photo_id"]);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   INSERT INTO bazaar.photo( url, seq_no ) 
+   VALUES ($1 , $2 ) ,
+          ($3 , $4 ) ,
+          ($5 , $6 ) 
+RETURNING photo_id
+".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+    assert_eq!(frag.params.len(), 6);
+}
+
+#[test]
+fn test_split_values(){
+    let mut query = Query::insert();
+    query.into_table("bazaar.photo")
+        .columns(vec!["url", "seq_no"]);
+    for i in 0..5{
+        query.value(&format!("img{}.jpg", i)).value(&i);
+    }
+    let queries = query.split_values(4).unwrap();
+    assert_eq!(queries.len(), 3);
+    assert_eq!(queries[0].values.len(), 4);
+    assert_eq!(queries[1].values.len(), 4);
+    assert_eq!(queries[2].values.len(), 2);
+}
+
+#[test]
+fn test_split_values_too_many_columns(){
+    let mut query = Query::insert();
+    query.into_table("bazaar.photo")
+        .columns(vec!["url", "seq_no", "caption"])
+        .value(&"img1.jpg").value(&1).value(&"front");
+    assert!(query.split_values(2).is_err());
+}
+
+#[test]
+fn test_values_not_fitting_columns(){
+    let db = Postgres::new();
+    let mut query = Query::insert();
+    query.into_table("bazaar.photo")
+        .columns(vec!["url", "seq_no"])
+        .value(&"img1.jpg").value(&1)
+        .value(&"img2.jpg");
+    assert!(query.build(&db).is_err());
+}
@@ -0,0 +1,117 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+use rustorm::query::Equality;
+
+
+#[test]
+fn test_declared_query(){
+    let db = Postgres::new();
+    let mut expensive = Query::select();
+    expensive.columns(vec!["product_id", "name"])
+        .from_table("bazaar.product")
+        .filter("price", Equality::GT, &1000);
+
+    let mut query = Query::select_all();
+    query.declare_query(expensive, "expensive")
+        .from_table("expensive")
+        .filter("name", Equality::LIKE, &"GTX%");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+     WITH expensive AS (
+   SELECT product_id, name
+     FROM bazaar.product
+    WHERE price > $1  )
+   SELECT *
+     FROM expensive
+    WHERE name LIKE $2 ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_recursive_declared_query(){
+    let db = Postgres::new();
+    let mut root = Query::select();
+    root.columns(vec!["category_id", "name", "parent_category_id"])
+        .from_table("bazaar.category")
+        .filter("name", Equality::EQ, &"Electronic");
+
+    let mut children = Query::select();
+    children.columns(vec!["category.category_id", "category.name", "category.parent_category_id"])
+        .from_table("bazaar.category")
+        .inner_join_table("category_tree", "category.parent_category_id", "category_tree.category_id");
+
+    let mut query = Query::select_all();
+    query.declare_recursive_query(root, children, "category_tree")
+        .from_table("category_tree");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+     WITH RECURSIVE category_tree AS (
+   SELECT category_id, name, parent_category_id
+     FROM bazaar.category
+    WHERE name = $1 
+UNION ALL 
+   SELECT category.category_id, category.name, category.parent_category_id
+     FROM bazaar.category
+          INNER JOIN category_tree 
+          ON category.parent_category_id = category_tree.category_id  )
+   SELECT *
+     FROM category_tree".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_recursive_declared_query_distinct(){
+    let db = Postgres::new();
+    let mut root = Query::select();
+    root.columns(vec!["category_id", "parent_category_id"])
+        .from_table("bazaar.category")
+        .filter("name", Equality::EQ, &"Electronic");
+
+    let mut children = Query::select();
+    children.columns(vec!["category.category_id", "category.parent_category_id"])
+        .from_table("bazaar.category")
+        .inner_join_table("category_tree", "category.parent_category_id", "category_tree.category_id");
+
+    let mut query = Query::select_all();
+    query.declare_recursive_query_distinct(root, children, "category_tree")
+        .from_table("category_tree");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+     WITH RECURSIVE category_tree AS (
+   SELECT category_id, parent_category_id
+     FROM bazaar.category
+    WHERE name = $1 
+    UNION 
+   SELECT category.category_id, category.parent_category_id
+     FROM bazaar.category
+          INNER JOIN category_tree 
+          ON category.parent_category_id = category_tree.category_id  )
+   SELECT *
+     FROM category_tree".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_declared_query_unsupported(){
+    let db = Mysql::new();
+    let mut expensive = Query::select_all();
+    expensive.from_table("product")
+        .filter("price", Equality::GT, &1000);
+
+    let mut query = Query::select_all();
+    query.declare_query(expensive, "expensive")
+        .from_table("expensive");
+    assert!(query.build(&db).is_err());
+}
@@ -0,0 +1,41 @@
+extern crate rustorm;
+
+use rustorm::database::{DbError, DbErrorKind};
+use std::error::Error;
+
+
+#[test]
+fn test_kind_from_sqlstate(){
+    assert_eq!(DbErrorKind::from_sqlstate("23505", Some("product_name_key".to_string()), None),
+        DbErrorKind::UniqueViolation{constraint: Some("product_name_key".to_string())});
+    assert_eq!(DbErrorKind::from_sqlstate("23503", None, None),
+        DbErrorKind::ForeignKeyViolation{constraint: None});
+    assert_eq!(DbErrorKind::from_sqlstate("23502", None, Some("name".to_string())),
+        DbErrorKind::NotNullViolation{column: Some("name".to_string())});
+    assert_eq!(DbErrorKind::from_sqlstate("42601", None, None), DbErrorKind::Syntax);
+    // undefined table and insufficient privilege are not syntax errors
+    assert_eq!(DbErrorKind::from_sqlstate("42P01", None, None), DbErrorKind::Other);
+    assert_eq!(DbErrorKind::from_sqlstate("42501", None, None), DbErrorKind::Other);
+    assert_eq!(DbErrorKind::from_sqlstate("08006", None, None), DbErrorKind::Connection);
+    assert_eq!(DbErrorKind::from_sqlstate("22P02", None, None), DbErrorKind::TypeConversion);
+    assert_eq!(DbErrorKind::from_sqlstate("P0001", None, None), DbErrorKind::Other);
+}
+
+#[test]
+fn test_error_cause(){
+    let io_error = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused");
+    let error = DbError::with_cause(DbErrorKind::Connection, "Unable to connect", io_error);
+    assert_eq!(error.kind(), &DbErrorKind::Connection);
+    assert!(error.cause().is_some());
+    assert_eq!(format!("{}", error), "Unable to connect, connection refused");
+    assert_eq!(DbError::not_found("no record").kind(), &DbErrorKind::NotFound);
+    assert!(DbError::new("something").cause().is_none());
+}
+
+#[test]
+fn test_driver_error_message(){
+    let io_error = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused");
+    let error = DbError::from_driver(DbErrorKind::Connection, io_error);
+    assert_eq!(error.description(), "database connection error");
+    assert_eq!(format!("{}", error), "database connection error, connection refused");
+}
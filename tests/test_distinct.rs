@@ -0,0 +1,52 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+
+
+#[test]
+fn test_distinct(){
+    let db = Mysql::new();
+    let mut query = Query::select();
+    query.distinct()
+        .column("name")
+        .from_table("product");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT DISTINCT name
+     FROM product".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_distinct_on(){
+    let db = Postgres::new();
+    let mut query = Query::select();
+    query.distinct_on_columns(&vec!["owner_id".to_string()])
+        .columns(vec!["owner_id", "name", "created"])
+        .from_table("bazaar.product")
+        .asc("owner_id")
+        .desc("created");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT DISTINCT ON (owner_id) owner_id, name, created
+     FROM bazaar.product
+ ORDER BY owner_id ASC, created DESC".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_distinct_on_unsupported(){
+    let db = Mysql::new();
+    let mut query = Query::select_all();
+    query.distinct_on_columns(&vec!["owner_id".to_string()])
+        .from_table("product");
+    assert!(query.build(&db).is_err());
+}
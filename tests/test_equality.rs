@@ -0,0 +1,64 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+
+
+#[test]
+fn test_equality_operators(){
+    let db = Postgres::new();
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .filter_between("price", &10, &100)
+        .filter_ilike("name", &"%gtx%")
+        .filter_not_like("description", &"%refurbished%")
+        .filter_regex("barcode", &"^[0-9]+$")
+        .filter_iregex("name", &"^nvidia")
+        .filter_distinct_from("owner_id", &"f7521093-734d-488a-9f60-fc9f11f7e750");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+    WHERE price BETWEEN $1  AND $2 
+      AND name ILIKE $3 
+      AND description NOT LIKE $4 
+      AND barcode ~ $5 
+      AND name ~* $6 
+      AND owner_id IS DISTINCT FROM $7 ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+    assert_eq!(frag.params.len(), 7);
+}
+
+#[test]
+fn test_equality_operators_emulated(){
+    let db = Mysql::new();
+    let mut query = Query::select_all();
+    query.from_table("product")
+        .filter_ilike("name", &"%gtx%")
+        .filter_distinct_from("owner_id", &1)
+        .filter_not_distinct_from("category_id", &2);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM product
+    WHERE lower(name) LIKE lower(?)
+      AND NOT (owner_id <=> ?)
+      AND category_id <=> ?".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_regex_unsupported(){
+    let db = Mysql::new();
+    let mut query = Query::select_all();
+    query.from_table("product")
+        .filter_regex("barcode", &"^[0-9]+$");
+    assert!(query.build(&db).is_err());
+}
@@ -0,0 +1,120 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+use rustorm::query::{Filter, Equality};
+
+
+#[test]
+fn test_any_and_not_all(){
+    let db = Postgres::new();
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .add_filter(Filter::any(vec![
+            Filter::new("price", Equality::LT, &100),
+            Filter::new("price", Equality::GT, &1000),
+        ]))
+        .add_filter(Filter::not(Filter::all(vec![
+            Filter::new("is_active", Equality::EQ, &false),
+            Filter::is_null("seq_no"),
+        ])));
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+    WHERE ( price < $1 OR price > $2 )
+      AND NOT ( is_active = $3 AND seq_no IS NULL )".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_minimal_parenthesis(){
+    let db = Mysql::new();
+    let mut query = Query::select_all();
+    query.from_table("product")
+        .add_filter(Filter::any(vec![
+            Filter::all(vec![
+                Filter::new("name", Equality::EQ, &"GTX660"),
+                Filter::not(Filter::new("price", Equality::GT, &100)),
+            ]),
+            Filter::all(vec![Filter::new("name", Equality::EQ, &"GTX980")]),
+        ]));
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM product
+    WHERE name = ? AND NOT price > ? OR name = ?".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_chained_builders(){
+    let db = Postgres::new();
+    let mut filter = Filter::new("name", Equality::EQ, &"GTX660");
+    filter.or("name", Equality::EQ, &"GTX980")
+        .and("price", Equality::LT, &100);
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .add_filter(filter);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+    WHERE name = $1 OR name = $2 AND price < $3".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_chained_builders_grouped(){
+    let db = Postgres::new();
+    let mut any = Filter::new("name", Equality::EQ, &"GTX660");
+    any.or("name", Equality::EQ, &"GTX980");
+    let filter = Filter::all(vec![any, Filter::new("price", Equality::LT, &100)]);
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .add_filter(filter);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+    WHERE ( name = $1 OR name = $2 ) AND price < $3".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_chaining_empty_groups(){
+    let db = Postgres::new();
+    let mut filter = Filter::any(vec![]);
+    filter.and("name", Equality::EQ, &"GTX660")
+        .or("name", Equality::EQ, &"GTX980")
+        .and_filter(Filter::all(vec![]));
+    let mut price = Filter::all(vec![]);
+    price.or("price", Equality::LT, &100);
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .add_filter(filter)
+        .add_filter(price);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+    WHERE ( name = $1 OR name = $2 )
+      AND price < $3".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
@@ -0,0 +1,27 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::query::Query;
+
+
+#[test]
+fn test_inner_join(){
+    let db = Postgres::new();
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .inner_join_table("bazaar.product_category", "product_category.product_id", "product.product_id")
+        .left_join_table("bazaar.category", "category.category_id", "product_category.category_id");
+    let frag = query.build(&db).unwrap();
+
+    // the join without a modifier is on its own line, the same as the modified joins
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+          INNER JOIN bazaar.product_category 
+          ON product_category.product_id = product.product_id 
+          LEFT JOIN bazaar.category 
+          ON category.category_id = product_category.category_id ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
@@ -0,0 +1,85 @@
+extern crate rustorm;
+extern crate rustc_serialize;
+
+use rustorm::platform::postgres::Postgres;
+#[cfg(feature = "sqlite")]
+use rustorm::platform::sqlite::Sqlite;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+use rustorm::query::{Equality, Filter, JsonPath};
+use rustc_serialize::json::Json;
+
+
+#[test]
+fn test_json_path_filters(){
+    let db = Postgres::new();
+    let mut query = Query::select();
+    query.column("name")
+        .json_path(JsonPath::text("info", vec!["dimension", "unit"]), "unit")
+        .from_table("bazaar.product")
+        .add_filter(Filter::json_path(JsonPath::text("info", vec!["color"]), Equality::EQ, &"red"))
+        .add_filter(Filter::has_key("info", "weight"))
+        .filter("info", Equality::CONTAINS, &Json::from_str("{\"weightUnit\":\"kg\"}").unwrap());
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT name, info->'dimension'->>'unit' AS unit
+     FROM bazaar.product
+    WHERE info->>'color' = $1 
+      AND info ? $2 
+      AND info @> $3 ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+    // jsonb only accepts json values, not text
+    assert_eq!(frag.params[2].variant_name(), "Json");
+}
+
+#[test]
+#[cfg(feature = "sqlite")]
+fn test_json_extract_sqlite(){
+    let db = Sqlite::new();
+    let mut query = Query::select();
+    query.column("name")
+        .from_table("product")
+        .add_filter(Filter::json_path(JsonPath::text("info", vec!["sizes"]).index(0), Equality::EQ, &"XL"));
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT name
+     FROM product
+    WHERE json_extract(info, '$.\"sizes\"[0]') = $1 ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_json_operators_unsupported(){
+    let db = Mysql::new();
+    let mut query = Query::select_all();
+    query.from_table("product")
+        .add_filter(Filter::has_key("info", "weight"));
+    assert!(query.build(&db).is_err());
+
+    let mut query = Query::select_all();
+    query.from_table("product")
+        .add_filter(Filter::json_path(JsonPath::text("info", vec!["color"]), Equality::EQ, &"red"));
+    assert!(query.build(&db).is_err());
+}
+
+#[test]
+fn test_json_numeric_key(){
+    let db = Postgres::new();
+    let mut query = Query::select();
+    query.json_path(JsonPath::json("info", vec!["sales", "2024"]).index(1), "sales")
+        .from_table("bazaar.product");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT info->'sales'->'2024'->1 AS sales
+     FROM bazaar.product".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
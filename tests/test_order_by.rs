@@ -0,0 +1,84 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+use rustorm::query::{Order, Operand, Direction, Function, ColumnName};
+
+
+#[test]
+fn test_order_by_nulls(){
+    let db = Postgres::new();
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .asc_nulls_first("seq_no")
+        .add_order(Order::new(Operand::Function(Function::with_columns("lower", vec!["name"])), Direction::DESC).nulls_last())
+        .asc("created");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+ ORDER BY seq_no ASC NULLS FIRST, lower(name) DESC NULLS LAST, created ASC".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_order_by_nulls_emulated(){
+    let db = Mysql::new();
+    let mut query = Query::select_all();
+    query.from_table("product")
+        .asc_nulls_last("seq_no")
+        .desc_nulls_first("created");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM product
+ ORDER BY CASE WHEN seq_no IS NULL THEN 1 ELSE 0 END, seq_no ASC, CASE WHEN created IS NULL THEN 0 ELSE 1 END, created DESC".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_order_by_schema_qualified_and_expression(){
+    let db = Postgres::new();
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .left_join_table("bazaar.category", "product.category_id", "category.category_id")
+        .asc("bazaar.product.name")
+        .order_by_sql("coalesce(product.price, category.price)", Direction::DESC);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+          LEFT JOIN bazaar.category 
+          ON product.category_id = category.category_id 
+ ORDER BY product.name ASC, coalesce(product.price, category.price) DESC".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_order_by_not_a_column(){
+    let db = Postgres::new();
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .desc("price; DROP TABLE product");
+    assert!(query.build(&db).is_err());
+
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .asc("a.bazaar.product.name");
+    assert!(query.build(&db).is_err());
+
+    assert!(ColumnName::try_from_str("a.bazaar.product.name").is_err());
+    assert!(ColumnName::try_from_str("coalesce(p.price, c.price)").is_err());
+    let name = ColumnName::try_from_str("bazaar.product.name").unwrap();
+    assert_eq!(name.super_complete_name(), "bazaar.product.name");
+}
@@ -0,0 +1,41 @@
+extern crate rustorm;
+
+use rustorm::database::{begin_sql, commit_sql, rollback_sql};
+use rustorm::database::{TransactionDepth, DbError};
+
+
+#[test]
+fn test_nested_transaction_sql(){
+    assert_eq!(begin_sql(0), "BEGIN");
+    assert_eq!(begin_sql(1), "SAVEPOINT sp_1");
+    assert_eq!(begin_sql(2), "SAVEPOINT sp_2");
+    // ending the innermost transaction releases or rolls back to its own savepoint
+    assert_eq!(rollback_sql(2), "ROLLBACK TO SAVEPOINT sp_2");
+    assert_eq!(commit_sql(1), "RELEASE SAVEPOINT sp_1");
+    assert_eq!(commit_sql(0), "COMMIT");
+    assert_eq!(rollback_sql(0), "ROLLBACK");
+}
+
+#[test]
+fn test_transaction_depth(){
+    let depth = TransactionDepth::new();
+    let mut executed = vec![];
+    depth.begin(|sql| { executed.push(sql.to_string()); Ok(()) }).unwrap();
+    depth.begin(|sql| { executed.push(sql.to_string()); Ok(()) }).unwrap();
+    assert_eq!(depth.get(), 2);
+    // a failed release keeps the savepoint, so it can still be rolled back
+    assert!(depth.commit(|_| Err(DbError::new("release failed"))).is_err());
+    assert_eq!(depth.get(), 2);
+    // a failed rollback to the savepoint keeps it, so the names still match the database
+    assert!(depth.rollback(|_| Err(DbError::new("rollback failed"))).is_err());
+    assert_eq!(depth.get(), 2);
+    depth.rollback(|sql| { executed.push(sql.to_string()); Ok(()) }).unwrap();
+    assert_eq!(depth.get(), 1);
+    // the transaction is over even when the rollback fails
+    assert!(depth.rollback(|_| Err(DbError::new("rollback failed"))).is_err());
+    assert_eq!(depth.get(), 0);
+    assert!(depth.commit(|_| Ok(())).is_err());
+    assert!(depth.begin(|_| Err(DbError::new("begin failed"))).is_err());
+    assert_eq!(depth.get(), 0);
+    assert_eq!(executed, vec!["BEGIN", "SAVEPOINT sp_1", "ROLLBACK TO SAVEPOINT sp_1", "RELEASE SAVEPOINT sp_1"]);
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "sqlite")]
+extern crate rustorm;
+
+use std::str::FromStr;
+use rustorm::pool::ManagedPool;
+use rustorm::query::Query;
+use rustorm::dao::Decimal;
+use rustorm::database::{Database, DatabaseDev};
+
+
+#[test]
+fn test_decimal_round_trip(){
+    let mut pool = ManagedPool::init("sqlite:///:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    let numeric = db.as_dev().rust_type_to_dbtype("Decimal").unwrap();
+    let create_sql = format!("CREATE TABLE product(product_id integer PRIMARY KEY, price {})", numeric);
+    db.as_ref().execute_sql(&create_sql, &vec![]).unwrap();
+
+    let price = Decimal::from_str("0.1234567890123456789").unwrap();
+    let mut insert = Query::insert();
+    insert.into_table("product")
+        .set("product_id", &1)
+        .set("price", &price);
+    insert.execute(db.as_ref()).unwrap();
+
+    let mut select = Query::select_all();
+    select.from_table("product");
+    let dao = select.retrieve_one(db.as_ref()).unwrap();
+    let saved: Decimal = dao.get("price");
+    assert_eq!(saved.to_string(), "0.1234567890123456789");
+}
@@ -0,0 +1,111 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::query::Query;
+use rustorm::query::{Filter, Equality, Operand, ColumnName, Field, TableName};
+
+
+#[test]
+fn test_in_subquery(){
+    let db = Postgres::new();
+    let mut photos = Query::select();
+    photos.column("product_id")
+        .from_table("bazaar.product_photo")
+        .filter("priority", Equality::GT, &1);
+
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .filter("name", Equality::LIKE, &"GTX%")
+        .add_filter(Filter::in_query("product_id", photos))
+        .filter("price", Equality::LT, &100);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+    WHERE name LIKE $1 
+      AND product_id IN (
+   SELECT product_id
+     FROM bazaar.product_photo
+    WHERE priority > $2  )
+      AND price < $3 ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+    assert_eq!(frag.params.len(), 3);
+}
+
+#[test]
+fn test_exists_and_derived_table(){
+    let db = Postgres::new();
+    let mut availability = Query::select();
+    availability.column("product_id")
+        .from_table("bazaar.product_availability")
+        .add_filter(Filter::bare_new(
+            Operand::ColumnName(ColumnName::from_str("product_availability.product_id")),
+            Equality::EQ,
+            Operand::ColumnName(ColumnName::from_str("product.product_id"))))
+        .filter("available", Equality::EQ, &true);
+    let mut available = Query::select();
+    available.columns(vec!["product_id", "name"])
+        .from_table("bazaar.product")
+        .add_filter(Filter::exists(availability));
+
+    let mut query = Query::select_all();
+    query.from_query(available, "available_product")
+        .filter("name", Equality::EQ, &"GTX660 Ti videocard");
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM (
+   SELECT product_id, name
+     FROM bazaar.product
+    WHERE EXISTS (
+   SELECT product_id
+     FROM bazaar.product_availability
+    WHERE product_id = product.product_id
+      AND available = $1  ) ) AS available_product
+    WHERE name = $2 ".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_correlated_self_join(){
+    let db = Postgres::new();
+    let mut cheaper = Query::select();
+    cheaper.column("product_id")
+        .from_field(Field{operand: Operand::TableName(TableName::from_str("bazaar.product")), name: Some("cheaper".to_string())})
+        .add_filter(Filter::bare_new(
+            Operand::ColumnName(ColumnName::from_str("cheaper.price")),
+            Equality::LT,
+            Operand::ColumnName(ColumnName::from_str("product.price"))));
+
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product")
+        .add_filter(Filter::not_exists(cheaper));
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   SELECT *
+     FROM bazaar.product
+    WHERE NOT EXISTS (
+   SELECT product_id
+     FROM bazaar.product AS cheaper
+    WHERE price < product.price )".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_own_column_schema(){
+    let mut query = Query::select_all();
+    query.from_table("bazaar.product");
+    assert!(query.is_own_column(&ColumnName::from_str("product.price")));
+    assert!(query.is_own_column(&ColumnName::from_str("bazaar.product.price")));
+    assert!(!query.is_own_column(&ColumnName::from_str("archive.product.price")));
+    assert!(!query.is_own_column(&ColumnName::from_str("category.price")));
+}
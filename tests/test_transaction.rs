@@ -0,0 +1,54 @@
+#![cfg(feature = "sqlite")]
+extern crate rustorm;
+
+use rustorm::pool::ManagedPool;
+use rustorm::query::Query;
+use rustorm::em::EntityManager;
+use rustorm::database::{Database, DbError};
+
+
+#[test]
+fn test_failed_transaction_leaves_no_rows(){
+    let mut pool = ManagedPool::init("sqlite:///:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref().execute_sql("CREATE TABLE product(product_id integer PRIMARY KEY, name text)", &vec![]).unwrap();
+
+    let em = EntityManager::new(db.as_ref());
+    let result: Result<(), DbError> = em.transaction(|em| {
+        let mut insert = Query::insert();
+        insert.into_table("product")
+            .set("product_id", &1)
+            .set("name", &"GTX660");
+        try!(insert.execute(em.db));
+        Err(DbError::new("out of stock"))
+    });
+    assert_eq!(format!("{}", result.unwrap_err()), "out of stock");
+
+    let mut select = Query::select_all();
+    select.from_table("product");
+    let products = select.retrieve(db.as_ref()).unwrap();
+    assert_eq!(products.dao.len(), 0);
+}
+
+#[test]
+fn test_failed_bulk_insert_leaves_no_rows(){
+    let mut pool = ManagedPool::init("sqlite:///:memory:", 1).unwrap();
+    let db = pool.connect().unwrap();
+    db.as_ref().execute_sql("CREATE TABLE photo(photo_id integer PRIMARY KEY)", &vec![]).unwrap();
+
+    // 1000 rows do not fit in the 999 parameters of one statement,
+    // the duplicate key in the last row fails the second statement
+    let mut insert = Query::insert();
+    insert.into_table("photo")
+        .column("photo_id");
+    for i in 0..999{
+        insert.value(&i);
+    }
+    insert.value(&0);
+    assert!(db.as_ref().insert_many(&insert).is_err());
+
+    let mut select = Query::select_all();
+    select.from_table("photo");
+    let photos = select.retrieve(db.as_ref()).unwrap();
+    assert_eq!(photos.dao.len(), 0);
+}
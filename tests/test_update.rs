@@ -0,0 +1,53 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+use rustorm::query::Query;
+use rustorm::query::Equality;
+use rustorm::dao::Value;
+
+
+#[test]
+fn test_update(){
+    let db = Postgres::new();
+    let mut query = Query::update();
+    query.from_table("bazaar.product")
+        .set("name", &"GTX660")
+        .set("price", &100)
+        .set("description", &"Graphics card")
+        .filter("product_id", Equality::EQ, &"f7521093-734d-488a-9f60-fc9f11f7e750")
+        .return_all();
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   UPDATE bazaar.product
+      SET name = $1 , price = $2 , description = $3 
+    WHERE product_id = $4 
+RETURNING *".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_update_exclude_columns(){
+    let db = Mysql::new();
+    let mut query = Query::update();
+    query.from_table("product")
+        .set("name", &"GTX660")
+        .set("price", &100)
+        .set("description", &"Graphics card")
+        .exclude_column("price")
+        .filter("product_id", Equality::EQ, &1);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   UPDATE product
+      SET name = ?, description = ?
+    WHERE product_id = ?".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+    assert_eq!(frag.params.len(), 3);
+    assert_eq!(frag.params[1], Value::String("Graphics card".to_string()));
+}
@@ -0,0 +1,94 @@
+extern crate rustorm;
+
+use rustorm::platform::postgres::Postgres;
+use rustorm::platform::mysql::Mysql;
+#[cfg(feature = "sqlite")]
+use rustorm::platform::sqlite::Sqlite;
+use rustorm::query::Query;
+
+
+#[test]
+fn test_upsert(){
+    let db = Postgres::new();
+    let mut query = Query::insert();
+    query.into_table("bazaar.product")
+        .columns(vec!["product_id", "name", "price"])
+        .value(&"f7521093-734d-488a-9f60-fc9f11f7e750").value(&"GTX660").value(&100)
+        .on_conflict(vec!["product_id"])
+        .return_all();
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   INSERT INTO bazaar.product( product_id, name, price ) 
+   VALUES ($1 , $2 , $3 ) 
+       ON CONFLICT (product_id) DO UPDATE
+      SET name = EXCLUDED.name, price = EXCLUDED.price
+RETURNING *
+".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_upsert_do_nothing(){
+    let db = Postgres::new();
+    let mut query = Query::insert();
+    query.into_table("bazaar.product_category")
+        .columns(vec!["product_id", "category_id"])
+        .value(&1).value(&2)
+        .on_conflict(vec!["product_id", "category_id"]);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   INSERT INTO bazaar.product_category( product_id, category_id ) 
+   VALUES ($1 , $2 ) 
+       ON CONFLICT (product_id, category_id) DO NOTHING
+".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[test]
+fn test_upsert_mysql(){
+    let db = Mysql::new();
+    let mut query = Query::insert();
+    query.into_table("product")
+        .columns(vec!["product_id", "name", "price"])
+        .value(&1).value(&"GTX660").value(&100)
+        .on_conflict(vec!["product_id"]);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   INSERT INTO product( product_id, name, price ) 
+   VALUES (?, ?, ?) 
+       ON DUPLICATE KEY UPDATE
+          name = VALUES(name), price = VALUES(price)
+".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_upsert_sqlite(){
+    let db = Sqlite::new();
+    let mut query = Query::insert();
+    query.into_table("product")
+        .columns(vec!["product_id", "name", "price"])
+        .value(&1).value(&"GTX660").value(&100)
+        .on_conflict(vec!["product_id"]);
+    let frag = query.build(&db).unwrap();
+
+    let expected = "
+   INSERT INTO product( product_id, name, price ) 
+   VALUES ($1 , $2 , $3 ) 
+       ON CONFLICT (product_id) DO UPDATE
+      SET name = EXCLUDED.name, price = EXCLUDED.price
+".to_string();
+    println!("actual:   {{\n{}}} [{}]", frag.sql, frag.sql.len());
+    println!("expected: {{{}}} [{}]", expected, expected.len());
+    assert!(frag.sql.trim() == expected.trim());
+}